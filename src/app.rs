@@ -1,22 +1,45 @@
 mod connected;
 mod song_info;
 mod queue;
+mod library;
+mod files;
+mod search;
+mod playlists;
+mod outputs;
+mod partitions;
+mod notifications;
+mod stats;
+mod details;
+mod list;
 mod progress;
 mod player;
+mod reconnect;
 
-use std::time::Duration;
+use std::path::PathBuf;
 use iced::{widget, Task, Element, Subscription};
-use crate::error::Error;
-use crate::mpd::{MpdEvent, MpdCtrl, mpd_connect};
+use crate::config::{Config, SeekStep};
+use crate::error::{Error, ErrorKind};
+use crate::mpd::{MpdEvent, MpdCtrl, MpdTarget, mpd_connect};
 
 use connected::{Connected, ConMsg};
+use reconnect::{overlay, Reconnect};
 
 #[derive(Debug, Clone)]
 pub enum AppMsg {
     Reconnect,
     Connect(MpdCtrl),
+    PartitionMissing(Error),
+    ShowLogin,
+    PasswordInput(String),
+    Login,
+    SwitchProfile(String),
     Operate(ConMsg),
+    Session(u64, Result<ConMsg, Error>),
+    SkipForward(SeekStep),
+    SkipBackward(SeekStep),
+    LayoutSaved(Result<(), String>),
     Error(Error),
+    Tick,
     Quit,
 }
 
@@ -34,103 +57,420 @@ impl From<Result<MpdEvent, Error>> for AppMsg {
         match result {
             Ok(MpdEvent::Connected(ctrl)) => AppMsg::Connect(ctrl),
             Ok(MpdEvent::Change(sub)) => AppMsg::Operate(ConMsg::Change(sub)),
+            Ok(MpdEvent::PartitionMissing(error)) => AppMsg::PartitionMissing(error),
             Err(error) => AppMsg::Error(error),
         }
     }
 }
 
+fn in_session(session: u64, task: Task<Result<ConMsg, Error>>) -> Task<AppMsg> {
+    task.map(move |result| AppMsg::Session(session, result))
+}
+
+
+pub struct App {
+    target: MpdTarget,
+    profile: Option<String>,
+    connection: Option<iced::task::Handle>,
+    config: Config,
+    config_path: Option<PathBuf>,
+    state: State,
+    // counts the fresh player states, results of tasks of an older one
+    // (like of another server) are dropped.
+    session: u64,
+}
 
-pub enum App {
+enum State {
     Unconnected,
-    Connected(Connected),
-    Error(Error),
+    Connected(Box<Connected>),
+    Login(String),
+    Reconnecting(Reconnect),
+    Error(Error, Option<Box<Connected>>),
 }
 
 impl App {
     const APP_NAME: &str = env!("CARGO_PKG_NAME");
     const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
-    const PERIODIC_REDRAW: Duration = Duration::from_millis(250);
 
-    pub fn new() -> (Self, Task<AppMsg>) {
-        (Self::Unconnected, Self::connect())
+    pub fn new(
+        target: MpdTarget,
+        profile: Option<String>,
+        config: Config,
+        config_path: Option<PathBuf>,
+    ) -> (Self, Task<AppMsg>) {
+        let mut app = Self {
+            target,
+            profile,
+            connection: None,
+            config,
+            config_path,
+            state: State::Unconnected,
+            session: 0,
+        };
+        let connect = app.connect();
+
+        (app, connect)
+    }
+
+    fn connect(&mut self) -> Task<AppMsg> {
+        let (task, handle) = mpd_connect(self.target.clone(), self.config.cover.binary_limit)
+            .map(AppMsg::from)
+            .abortable();
+
+        self.connection = Some(handle.abort_on_drop());
+        task
     }
 
-    fn connect() -> Task<AppMsg> {
-        mpd_connect().map(AppMsg::from)
+    pub fn theme(&self) -> iced::Theme {
+        self.config.theme()
+    }
+
+    fn save_layout(&mut self) -> Task<AppMsg> {
+        let State::Connected(con) = &self.state else {
+            return Task::none();
+        };
+
+        if self.config.layout == *con.layout() {
+            return Task::none();
+        }
+        self.config.layout = con.layout().clone();
+
+        let Some(path) = self.config_path.clone() else {
+            return Task::none();
+        };
+
+        Task::perform(
+            self.config.layout.clone().save(path),
+            AppMsg::LayoutSaved,
+        )
     }
 
     pub fn title(&self) -> String {
-        let title = match self {
-            Self::Unconnected => "Unconnected",
-            Self::Connected(con) => con.title(),
-            Self::Error(_) => "Error",
+        let title = match &self.state {
+            State::Unconnected => String::from("Unconnected"),
+            State::Connected(con) => con.title(),
+            State::Login(_) => String::from("Login"),
+            State::Reconnecting(_) => String::from("Reconnecting"),
+            State::Error(..) => String::from("Error"),
         };
 
         format!("{} {} - {}", Self::APP_NAME, Self::APP_VERSION, title)
     }
 
+    fn connection_lost(&mut self, error: Error) {
+        let state = std::mem::replace(&mut self.state, State::Unconnected);
+        self.state = match state {
+            State::Reconnecting(mut reconnect) => {
+                reconnect.failed(error);
+                State::Reconnecting(reconnect)
+            }
+
+            State::Connected(con) => {
+                // come back to the partition we switched to
+                if let Some(partition) = con.partition() {
+                    self.target.partition = Some(partition.to_owned());
+                }
+                State::Reconnecting(Reconnect::new(error, Some(con)))
+            }
+
+            _ => State::Reconnecting(Reconnect::new(error, None)),
+        };
+    }
+
     pub fn update(&mut self, message: AppMsg) -> Task<AppMsg> {
         match message {
             AppMsg::Reconnect => {
-                *self = Self::Unconnected;
-                Self::connect()
+                let state = std::mem::replace(&mut self.state, State::Unconnected);
+                self.state = match state {
+                    State::Reconnecting(mut reconnect) => {
+                        reconnect.retry_now();
+                        State::Reconnecting(reconnect)
+                    }
+
+                    // carry on with the player we had
+                    State::Error(error, Some(last)) => {
+                        let mut reconnect = Reconnect::new(error, Some(last));
+                        reconnect.retry_now();
+                        State::Reconnecting(reconnect)
+                    }
+
+                    _ => State::Unconnected,
+                };
+                self.connect()
             }
 
             AppMsg::Connect(ctrl) => {
-                let con = Connected::new(ctrl);
-                let request_queue = con.request_queue()
-                    .map(AppMsg::from);
+                let last = match &mut self.state {
+                    State::Reconnecting(reconnect) => reconnect.take_last(),
+                    _ => None,
+                };
+
+                let con = match last {
+                    Some(mut con) => {
+                        tracing::info!("connection to mpd reestablished");
+                        con.reconnected(ctrl);
+                        con
+                    }
+                    None => Box::new(Connected::new(
+                        ctrl,
+                        &self.config,
+                        &self.target.endpoint.to_string(),
+                    )),
+                };
+
+                let requests = in_session(self.session, Task::batch([
+                    con.request_permissions(),
+                    con.request_queue(),
+                    con.request_modes(),
+                ]));
+
+                self.state = State::Connected(con);
+                requests
+            }
 
-                *self = Self::Connected(con);
-                request_queue
+            AppMsg::PartitionMissing(error) => {
+                self.target.partition = None;
+                match &mut self.state {
+                    State::Connected(con) => in_session(self.session, con.show_error(error)),
+                    _ => Task::none(),
+                }
             }
 
-            AppMsg::Operate(msg) => match self {
-                Self::Connected(c) => c.update(msg).map(AppMsg::from),
-                _ => Task::none(),
+            AppMsg::ShowLogin => {
+                let password = self.target.password.clone().unwrap_or_default();
+                self.state = State::Login(password);
+                Task::none()
+            }
+
+            AppMsg::PasswordInput(input) => {
+                if let State::Login(password) = &mut self.state {
+                    *password = input;
+                }
+                Task::none()
+            }
+
+            AppMsg::Login => {
+                if let State::Login(password) = &mut self.state {
+                    self.target.password = Some(std::mem::take(password))
+                        .filter(|p| !p.is_empty());
+                }
+                self.state = State::Unconnected;
+                self.session += 1;
+                self.connect()
+            }
+
+            AppMsg::SwitchProfile(name) => {
+                let target = match self.config.connection(Some(&name)) {
+                    Ok(connection) => MpdTarget::from_config(connection),
+                    Err(error) => {
+                        tracing::error!("{error}");
+                        return Task::none();
+                    }
+                };
+
+                tracing::info!("switching to profile {name}");
+                self.target = target;
+                self.profile = Some(name);
+                self.state = State::Unconnected;
+                self.session += 1;
+                self.connect()
+            }
+
+            AppMsg::Session(session, _) if session != self.session => {
+                tracing::debug!("dropping result of session {session}");
+                Task::none()
+            }
+
+            AppMsg::Session(_, result) => self.update(result.into()),
+
+            AppMsg::Operate(msg) => {
+                let layout_changed = matches!(&msg, ConMsg::Toggle(t) if t.is_layout());
+
+                let task = match &mut self.state {
+                    State::Connected(c) => in_session(self.session, c.update(msg)),
+                    _ => Task::none(),
+                };
+
+                if layout_changed {
+                    Task::batch([task, self.save_layout()])
+                } else {
+                    task
+                }
+            }
+
+            AppMsg::SkipForward(step) => {
+                use crate::mpd::Cmd;
+                let d = self.config.seek.get(step);
+                self.update(AppMsg::Operate(ConMsg::Cmd(Cmd::SkipForward(d))))
+            }
+
+            AppMsg::SkipBackward(step) => {
+                use crate::mpd::Cmd;
+                let d = self.config.seek.get(step);
+                self.update(AppMsg::Operate(ConMsg::Cmd(Cmd::SkipBackward(d))))
+            }
+
+            AppMsg::LayoutSaved(result) => {
+                if let Err(error) = result {
+                    tracing::warn!("failed to save layout: {error}");
+                }
+                Task::none()
             }
 
-            AppMsg::Error(error) => {
-                *self = Self::Error(error);
+            // requests made before the connection was lost may still fail
+            AppMsg::Error(error) if matches!(&self.state,
+                State::Reconnecting(reconnect) if reconnect.is_waiting()
+            ) => {
+                tracing::debug!("ignoring error while reconnecting: {error}");
                 Task::none()
             }
 
+            AppMsg::Error(error) => match error.kind() {
+                ErrorKind::Retryable => {
+                    tracing::warn!("lost connection to mpd: {error}");
+                    self.connection_lost(error);
+                    Task::none()
+                }
+
+                ErrorKind::Permission if !matches!(self.state, State::Connected(_)) => {
+                    tracing::warn!("mpd rejected our password: {error}");
+                    self.state = State::Login(String::new());
+                    Task::none()
+                }
+
+                _ => match &mut self.state {
+                    // the connection is still fine, only this request failed
+                    State::Connected(con) => in_session(self.session, con.show_error(error)),
+
+                    // trying again would fail the same way
+                    State::Reconnecting(reconnect) => {
+                        tracing::error!("reconnecting failed: {error}");
+                        let last = reconnect.take_last();
+                        self.state = State::Error(error, last);
+                        Task::none()
+                    }
+
+                    _ => {
+                        self.state = State::Error(error, None);
+                        Task::none()
+                    }
+                }
+            }
+
+            AppMsg::Tick => match &mut self.state {
+                State::Reconnecting(reconnect) => if reconnect.is_due() {
+                    self.connect()
+                } else {
+                    Task::none()
+                }
+                _ => Task::none(),
+            }
+
             AppMsg::Quit => {
                 std::process::exit(0);
             }
         }
     }
 
-    pub fn view(&self) -> Element<AppMsg> {
-        let content: Element<_> = match self {
-            Self::Unconnected
+    pub fn view(&self) -> Element<'_, AppMsg> {
+        let content: Element<_> = match &self.state {
+            State::Unconnected
                 => widget::text("Connecting to MPD").size(20).into(),
 
-            Self::Connected(con) => con.view().map(AppMsg::Operate),
+            State::Connected(con) => con.view().map(AppMsg::Operate),
 
-            Self::Error(error) => widget::Column::new()
+            State::Reconnecting(reconnect) => {
+                let last = reconnect
+                    .last()
+                    .map(|con| con.view().map(AppMsg::Operate));
+
+                reconnect.view(AppMsg::Reconnect, last)
+            }
+
+            State::Login(password) => widget::Column::new()
                 .spacing(20)
+                .width(300)
                 .align_x(iced::Center)
-                .push(widget::text("Error").size(40))
-                .push(widget::text(error.to_string()).size(20))
-                .push(widget::button("Reconnect").on_press(AppMsg::Reconnect))
+                .push(widget::text("Password").size(40))
+                .push(widget::text_input("MPD password", password)
+                    .secure(true)
+                    .on_input(AppMsg::PasswordInput)
+                    .on_submit(AppMsg::Login))
+                .push(widget::button("Log in").on_press(AppMsg::Login))
                 .into(),
+
+            State::Error(error, last) => {
+                let info = widget::Column::new()
+                    .spacing(20)
+                    .align_x(iced::Center)
+                    .push(widget::text("Error").size(40))
+                    .push(widget::text(error.to_string()).size(20))
+                    .push(widget::button("Reconnect").on_press(AppMsg::Reconnect));
+
+                let last = last
+                    .as_ref()
+                    .map(|con| con.view().map(AppMsg::Operate));
+
+                overlay(info, last)
+            }
+        };
+
+        widget::Column::new()
+            .push_maybe(self.view_header())
+            .push(widget::center(content))
+            .into()
+    }
+
+    fn view_header(&self) -> Option<Element<'_, AppMsg>> {
+        use iced::{Center, Fill};
+
+        let restricted = match &self.state {
+            State::Connected(con) => con.permissions().is_restricted(),
+            _ => false,
         };
 
-        widget::center(content).into()
+        let login = restricted.then(|| widget::Row::new()
+            .spacing(10)
+            .align_y(Center)
+            .push(widget::text("Read-only access").size(14))
+            .push(widget::button(widget::text("Log in").size(14))
+                .on_press(AppMsg::ShowLogin))
+        );
+
+        let profiles = self.config.profile_names();
+        let switcher = (!profiles.is_empty()).then(|| {
+            widget::pick_list(profiles, self.profile.clone(), AppMsg::SwitchProfile)
+                .placeholder("Server")
+                .text_size(14)
+        });
+
+        if login.is_none() && switcher.is_none() {
+            return None;
+        }
+
+        Some(widget::Row::new()
+            .padding([10, 20])
+            .spacing(10)
+            .align_y(Center)
+            .push_maybe(login)
+            .push(widget::horizontal_space().width(Fill))
+            .push_maybe(switcher)
+            .into())
     }
 
     pub fn subscriptions(&self) -> Subscription<AppMsg> {
         Subscription::batch([
             self.subscribe_redraw_timer(),
             self.subscribe_keyboard(),
+            self.subscribe_modifiers(),
         ])
     }
 
     fn subscribe_keyboard(&self) -> Subscription<AppMsg> {
         use iced::keyboard::{Key, Modifiers, key::Named};
         use crate::mpd::Cmd;
-        use connected::Toggle;
+        use connected::{Toggle, Adjust, Panel};
+        use queue::QueueMsg;
 
         iced::keyboard::on_key_press(|k, m| {
             match (k, m) {
@@ -140,22 +480,25 @@ impl App {
                 (Key::Named(Named::Space), _)
                     => Some(AppMsg::Operate(ConMsg::Toggle(Toggle::Play))),
 
+                (Key::Named(Named::Delete), _)
+                    => Some(AppMsg::Operate(ConMsg::Queue(QueueMsg::RemoveSelected))),
+
                 (Key::Named(Named::ArrowLeft), mods) => {
-                    let d = match mods {
-                        Modifiers::SHIFT => Duration::from_secs(30),
-                        Modifiers::CTRL => Duration::from_secs(300),
-                        _ => Duration::from_secs(10),
+                    let step = match mods {
+                        Modifiers::SHIFT => SeekStep::Medium,
+                        Modifiers::CTRL => SeekStep::Long,
+                        _ => SeekStep::Short,
                     };
-                    Some(AppMsg::Operate(ConMsg::Cmd(Cmd::SkipBackward(d))))
+                    Some(AppMsg::SkipBackward(step))
                 }
 
                 (Key::Named(Named::ArrowRight), mods) => {
-                    let d = match mods {
-                        Modifiers::SHIFT => Duration::from_secs(30),
-                        Modifiers::CTRL => Duration::from_secs(300),
-                        _ => Duration::from_secs(10),
+                    let step = match mods {
+                        Modifiers::SHIFT => SeekStep::Medium,
+                        Modifiers::CTRL => SeekStep::Long,
+                        _ => SeekStep::Short,
                     };
-                    Some(AppMsg::Operate(ConMsg::Cmd(Cmd::SkipForward(d))))
+                    Some(AppMsg::SkipForward(step))
                 }
 
                 (Key::Character(v), Modifiers::CTRL) if v == "q"
@@ -171,6 +514,26 @@ impl App {
                     "r" => Some(AppMsg::Operate(ConMsg::Toggle(Toggle::Random))),
                     "l" => Some(AppMsg::Operate(ConMsg::Toggle(Toggle::Loop))),
                     "c" => Some(AppMsg::Operate(ConMsg::Toggle(Toggle::Consume))),
+                    "s" => Some(AppMsg::Operate(ConMsg::Toggle(Toggle::Single))),
+                    "g" => Some(AppMsg::Operate(ConMsg::Toggle(Toggle::ReplayGain))),
+                    "x" => Some(AppMsg::Operate(ConMsg::Adjust(Adjust::Crossfade, 1))),
+                    "X" => Some(AppMsg::Operate(ConMsg::Adjust(Adjust::Crossfade, -1))),
+                    "m" => Some(AppMsg::Operate(ConMsg::Adjust(Adjust::MixRampDb, 1))),
+                    "M" => Some(AppMsg::Operate(ConMsg::Adjust(Adjust::MixRampDb, -1))),
+                    "d" => Some(AppMsg::Operate(ConMsg::Adjust(Adjust::MixRampDelay, 1))),
+                    "D" => Some(AppMsg::Operate(ConMsg::Adjust(Adjust::MixRampDelay, -1))),
+                    "u" => Some(AppMsg::Operate(ConMsg::Cmd(Cmd::Update(None)))),
+                    "U" => Some(AppMsg::Operate(ConMsg::Cmd(Cmd::Rescan(None)))),
+                    "1" => Some(AppMsg::Operate(ConMsg::ShowPanel(Panel::Player))),
+                    "2" => Some(AppMsg::Operate(ConMsg::ShowPanel(Panel::Queue))),
+                    "3" => Some(AppMsg::Operate(ConMsg::ShowPanel(Panel::Library))),
+                    "4" => Some(AppMsg::Operate(ConMsg::ShowPanel(Panel::Files))),
+                    "5" => Some(AppMsg::Operate(ConMsg::ShowPanel(Panel::Search))),
+                    "6" => Some(AppMsg::Operate(ConMsg::ShowPanel(Panel::Playlists))),
+                    "7" => Some(AppMsg::Operate(ConMsg::ShowPanel(Panel::Outputs))),
+                    "8" => Some(AppMsg::Operate(ConMsg::ShowPanel(Panel::Partitions))),
+                    "9" => Some(AppMsg::Operate(ConMsg::ShowPanel(Panel::Stats))),
+                    "0" => Some(AppMsg::Operate(ConMsg::ShowPanel(Panel::Details))),
                     _ => None,
                 },
 
@@ -179,13 +542,30 @@ impl App {
         })
     }
 
+    fn subscribe_modifiers(&self) -> Subscription<AppMsg> {
+        use iced::{Event, keyboard};
+
+        iced::event::listen_with(|event, _status, _window| match event {
+            Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers))
+                => Some(AppMsg::Operate(ConMsg::Modifiers(modifiers))),
+
+            _ => None,
+        })
+    }
+
     fn subscribe_redraw_timer(&self) -> Subscription<AppMsg> {
-        match self {
-            App::Connected(con) if con.is_playing() => {
-                iced::time::every(Self::PERIODIC_REDRAW)
+        match &self.state {
+            State::Connected(con) if con.is_playing() => {
+                iced::time::every(self.config.redraw_interval())
                     .map(|_| AppMsg::Operate(ConMsg::Redraw))
             }
 
+            // keep the reconnect countdown running
+            State::Reconnecting(_) => {
+                iced::time::every(self.config.redraw_interval())
+                    .map(|_| AppMsg::Tick)
+            }
+
             _ => Subscription::none(),
         }
     }
@@ -1,4 +1,5 @@
-use iced::{Task, Element};
+use std::collections::{HashMap, HashSet};
+use iced::{keyboard::Modifiers, task, widget, Task, Element, Theme};
 use bytes::BytesMut;
 use mpd_client::{
     responses::{
@@ -6,13 +7,69 @@ use mpd_client::{
         SongInQueue,
     },
     client::Subsystem,
-    commands::SongId,
+    commands::{SongId, ReplayGainMode},
 };
 
-use crate::mpd::{MpdCtrl, Cmd, CmdResult};
-use crate::error::Error;
+use crate::config::{Config, Layout, FormatConfig};
+use crate::cover::{CoverLookup, CoverStore, DiskCache};
+use crate::mpd::{MpdCtrl, Cmd, CmdResult, MixRamp, AudioStatus, Permissions};
+use crate::error::{Error, ErrorKind};
 use super::player::Player;
-use super::queue::Queue;
+use super::queue::{Queue, QueueMsg};
+use super::library::{Library, LibraryMsg};
+use super::files::{Files, FilesMsg};
+use super::search::{Search, SearchMsg};
+use super::playlists::{Playlists, PlaylistsMsg};
+use super::outputs::{Outputs, OutputsMsg};
+use super::partitions::{Partitions, PartitionsMsg};
+use super::notifications::Notifications;
+use super::stats::{StatsView, StatsMsg};
+use super::details::Details;
+use super::song_info::SongInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Panel {
+    Player,
+    Queue,
+    Library,
+    Files,
+    Search,
+    Playlists,
+    Outputs,
+    Partitions,
+    Stats,
+    Details,
+}
+
+impl Panel {
+    pub const ALL: [Panel; 10] = [
+        Panel::Player,
+        Panel::Queue,
+        Panel::Library,
+        Panel::Files,
+        Panel::Search,
+        Panel::Playlists,
+        Panel::Outputs,
+        Panel::Partitions,
+        Panel::Stats,
+        Panel::Details,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Panel::Player => "Player",
+            Panel::Queue => "Queue",
+            Panel::Library => "Library",
+            Panel::Files => "Files",
+            Panel::Search => "Search",
+            Panel::Playlists => "Playlists",
+            Panel::Outputs => "Outputs",
+            Panel::Partitions => "Partitions",
+            Panel::Stats => "Stats",
+            Panel::Details => "Details",
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum Toggle {
@@ -26,6 +83,26 @@ pub enum Toggle {
     Random,
     Loop,
     Consume,
+    Single,
+    ReplayGain,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Adjust {
+    Crossfade,
+    MixRampDb,
+    MixRampDelay,
+}
+
+impl Toggle {
+    pub fn is_layout(&self) -> bool {
+        matches!(self,
+            Toggle::ShowOptions
+            | Toggle::ShowSongInfo
+            | Toggle::ShowCoverArt
+            | Toggle::ShowProgress
+        )
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -38,32 +115,117 @@ pub enum ConMsg {
     UpdateSongInfo(Status),
     UpdateQueue(Vec<SongInQueue>),
     UpdateStatus(Status),
-    UpdateCoverArt(SongId, Option<BytesMut>),
+    UpdateModes(MixRamp, ReplayGainMode),
+    Adjust(Adjust, i32),
+    Dismiss(u64),
+    UpdateCoverArt(String, Option<BytesMut>),
+    CoverArtFailed(String, Error),
+    UpdatePermissions(Permissions),
+    ShowPanel(Panel),
+    Modifiers(Modifiers),
+    Queue(QueueMsg),
+    Library(LibraryMsg),
+    Files(FilesMsg),
+    Search(SearchMsg),
+    Playlists(PlaylistsMsg),
+    Outputs(OutputsMsg),
+    Partitions(PartitionsMsg),
+    Stats(StatsMsg),
+    UpdateAudio(AudioStatus),
 }
 
 pub struct Connected {
     ctrl: MpdCtrl,
+    permissions: Permissions,
+    panel: Panel,
     player: Player,
     queue: Queue,
+    library: Library,
+    files: Files,
+    search: Search,
+    playlists: Playlists,
+    outputs: Outputs,
+    partitions: Partitions,
+    stats: StatsView,
+    details: Details,
+    notifications: Notifications,
+    updating: bool,
+    formats: FormatConfig,
+    announced: Option<SongId>,
+    // cover art being downloaded by cover key, dropping a handle
+    // cancels the download.
+    cover_requests: HashMap<String, task::Handle>,
+    covers: CoverStore,
+    cover_lookup: CoverLookup,
+    prefetch: usize,
+    prefetch_concurrency: usize,
+    cover_cache: DiskCache,
 }
 
 impl Connected {
-    pub fn new(ctrl: MpdCtrl) -> Self {
+    pub fn new(ctrl: MpdCtrl, config: &Config, server: &str) -> Self {
+        let budget = config.cover.memory_budget.saturating_mul(1024 * 1024);
+
         Self {
             ctrl,
-            player: Player::new(),
+            permissions: Permissions::unknown(),
+            panel: Panel::Player,
+            player: Player::new(config.layout.clone()),
             queue: Queue::default(),
+            library: Library::new(),
+            files: Files::new(),
+            search: Search::new(),
+            playlists: Playlists::new(),
+            outputs: Outputs::new(),
+            partitions: Partitions::new(),
+            stats: StatsView::new(),
+            details: Details::default(),
+            notifications: Notifications::default(),
+            updating: false,
+            formats: config.format.clone(),
+            announced: None,
+            cover_requests: HashMap::new(),
+            covers: CoverStore::new(budget),
+            cover_cache: DiskCache::new(&config.cover, server),
+            cover_lookup: CoverLookup::new(&config.cover),
+            prefetch: config.cover.prefetch,
+            prefetch_concurrency: config.cover.prefetch_concurrency.max(1),
         }
     }
 
+    pub fn reconnected(&mut self, ctrl: MpdCtrl) {
+        self.ctrl = ctrl;
+        self.permissions = Permissions::unknown();
+        // downloads of the old connection never finish
+        self.cover_requests.clear();
+    }
+
     pub fn is_playing(&self) -> bool {
         self.player.is_playing()
     }
 
-    pub fn title(&self) -> &str {
-        self.player
-            .get_song_title()
-            .unwrap_or("Empty")
+    pub fn layout(&self) -> &Layout {
+        self.player.layout()
+    }
+
+    pub fn permissions(&self) -> &Permissions {
+        &self.permissions
+    }
+
+    pub fn title(&self) -> String {
+        let title = self.player
+            .get_song_info()
+            .map(|info| self.formats.window_title.render(info))
+            .unwrap_or(String::from("Empty"));
+
+        match self.player.get_partition() {
+            Some(partition) => format!("{title} [{partition}]"),
+            None => title,
+        }
+    }
+
+    pub fn partition(&self) -> Option<&str> {
+        self.player.get_partition()
     }
 
     pub fn update(&mut self, msg: ConMsg) -> Task<Result<ConMsg, Error>> {
@@ -73,16 +235,41 @@ impl Connected {
 
                 use mpd_client::client::Subsystem;
                 match sub {
+                    Subsystem::Player if self.panel == Panel::Details => Task::batch([
+                        self.request_song_info(),
+                        self.request_audio_status(),
+                    ]),
                     Subsystem::Player => self.request_song_info(),
                     Subsystem::Queue => self.request_queue(),
                     Subsystem::Mixer => self.request_status(),
-                    Subsystem::Options => self.request_status(),
+                    Subsystem::Options => Task::batch([
+                        self.request_status(),
+                        self.request_modes(),
+                    ]),
+                    Subsystem::StoredPlaylist => self.playlists.refresh(&self.ctrl),
+                    Subsystem::Output => Task::batch([
+                        self.outputs.refresh(&self.ctrl),
+                        self.partitions.refresh(&self.ctrl),
+                    ]),
+                    Subsystem::Partition => self.partitions.refresh(&self.ctrl),
+                    Subsystem::Update => self.request_status(),
+                    Subsystem::Database => Task::batch([
+                        self.library.refresh(&self.ctrl),
+                        self.files.refresh(&self.ctrl),
+                        self.search.refresh(&self.ctrl),
+                        self.stats.refresh(&self.ctrl),
+                    ]),
 
                     _ => Task::none(),
                 }
             }
 
             ConMsg::Cmd(cmd) => {
+                if !self.permissions.allows(cmd.mpd_command()) {
+                    tracing::warn!("command {cmd:?} not permitted");
+                    return Task::none();
+                }
+
                 // to make the volume and position slider react faster we
                 // inject the user requested value back before the server
                 // supplies us with the real value (which should be identical).
@@ -101,32 +288,55 @@ impl Connected {
 
             ConMsg::CmdResult(CmdResult { cmd, error }) => {
                 tracing::debug!("command {cmd:?} completed");
-                if let Some(msg) = error {
-                    tracing::warn!("command {cmd:?} returned error: {msg}");
-                    self.request_status()
+                if let Some(error) = error {
+                    tracing::warn!("command {cmd:?} returned error: {error}");
+                    let text = format!("{} failed: {}", cmd.mpd_command(), describe(&error));
+                    Task::batch([self.notify_error(text), self.request_status()])
+                } else if let Cmd::SwitchPartition(_) = cmd {
+                    // queue, player and outputs are those of the new partition
+                    Task::batch([
+                        self.request_queue(),
+                        self.request_modes(),
+                        self.outputs.refresh(&self.ctrl),
+                        self.partitions.refresh(&self.ctrl),
+                    ])
                 } else {
                     Task::none()
                 }
             }
 
-            ConMsg::Redraw => Task::none(),
+            ConMsg::Redraw => {
+                // format and bitrate change while playing
+                if self.panel == Panel::Details && self.details.is_due() {
+                    self.request_audio_status()
+                } else {
+                    Task::none()
+                }
+            }
 
             ConMsg::Toggle(t) => self.toggle(t),
 
             ConMsg::UpdateSongInfo(status) => {
                 tracing::debug!("update song information");
 
-                self.player.update_status(status);
+                let finished = self.update_status(status);
 
-                if let Some(id) = self.player.get_current_id() {
+                let task = if let Some(id) = self.player.get_current_id() {
                     if let Some(info) = self.queue.get(&id) {
                         self.player.set_song_info(info.clone());
-                        if info.is_cover_missing() {
-                            self.request_cover_art(id)
-                        } else if let Some(next) = self.player.get_next_id() {
-                            self.request_cover_art(next)
-                        } else {
-                            Task::none()
+
+                        let announce = self.formats.notification
+                            .as_ref()
+                            .filter(|_| self.announced != Some(id))
+                            .map(|format| format.render(info));
+                        self.announced = Some(id);
+
+                        self.covers.touch(&info.cover_key());
+                        let cover = self.request_covers();
+
+                        match announce {
+                            Some(text) => Task::batch([cover, self.notify(text)]),
+                            None => cover,
                         }
                     } else {
                         tracing::error!("current song {} not in queue", id.0);
@@ -135,8 +345,10 @@ impl Connected {
 
                 } else {
                     self.player.clear_song_info();
-                    Task::none()
-                }
+                    self.request_covers()
+                };
+
+                Task::batch([task, finished])
             }
 
             ConMsg::UpdateQueue(queue) => {
@@ -147,43 +359,236 @@ impl Connected {
 
             ConMsg::UpdateStatus(status) => {
                 tracing::debug!("update player status");
-                self.player.update_status(status);
+                self.update_status(status)
+            }
+
+            ConMsg::UpdateCoverArt(key, data) => {
+                tracing::debug!("update cover art of {key}");
+                self.cover_requests.remove(&key);
+                self.covers.insert(key, data);
+                self.request_covers()
+            }
+
+            ConMsg::CoverArtFailed(key, error) => {
+                // allow to try again
+                self.cover_requests.remove(&key);
+                Task::done(Err(error))
+            }
+
+            ConMsg::Dismiss(id) => {
+                self.notifications.dismiss(id);
                 Task::none()
             }
 
-            ConMsg::UpdateCoverArt(id, data) => {
-                tracing::debug!("update cover art for id {}", id.0);
-                self.queue.update_coverart(id, data);
+            ConMsg::UpdateModes(mixramp, replay_gain) => {
+                self.player.update_modes(mixramp, replay_gain);
+                Task::none()
+            }
 
-                if self.player.get_current_id() == Some(id) {
-                    // we got the current cover, update player
-                    if let Some(info) = self.queue.get(&id) {
-                        self.player.set_song_info(info.clone());
-                    } else {
-                        tracing::error!("current song {} not in queue", id.0);
-                    }
+            ConMsg::Adjust(adjust, delta) => match self.player.adjust(adjust, delta) {
+                Some(cmd) => self.update(ConMsg::Cmd(cmd)),
+                None => Task::none(),
+            },
 
-                    // now also try to retrieve cover for next song
-                    if let Some(next_id) = self.player.get_next_id() {
-                        self.request_cover_art(next_id)
-                    } else {
-                        Task::none()
-                    }
+            ConMsg::UpdatePermissions(permissions) => {
+                tracing::debug!("update permissions: {permissions:?}");
+                self.permissions = permissions;
+                Task::none()
+            }
 
-                } else {
-                    // we got the cover for something else
-                    if let Some(current_id) = self.player.get_current_id() {
-                        self.request_cover_art(current_id)
-                    } else {
-                        Task::none()
-                    }
+            ConMsg::ShowPanel(panel) => {
+                self.panel = panel;
+                let load = match panel {
+                    Panel::Library => self.library.load(&self.ctrl),
+                    Panel::Files => self.files.load(&self.ctrl),
+                    Panel::Playlists => self.playlists.load(&self.ctrl),
+                    Panel::Outputs => self.outputs.load(&self.ctrl),
+                    Panel::Partitions => self.partitions.load(&self.ctrl),
+                    Panel::Stats => self.stats.load(&self.ctrl),
+                    Panel::Details => self.request_audio_status(),
+                    _ => Task::none(),
+                };
+                Task::batch([load, self.request_covers()])
+            }
+
+            ConMsg::Modifiers(modifiers) => {
+                self.queue.set_modifiers(modifiers);
+                Task::none()
+            }
+
+            ConMsg::Queue(msg) => {
+                // keyboard shortcuts only apply to the visible queue
+                if matches!(msg, QueueMsg::RemoveSelected) && self.panel != Panel::Queue {
+                    return Task::none();
+                }
+
+                let scrolled = matches!(msg, QueueMsg::Scroll(_));
+                match self.queue.handle(msg) {
+                    Some(cmd) => self.update(ConMsg::Cmd(cmd)),
+                    None if scrolled => self.request_covers(),
+                    None => Task::none(),
                 }
             }
+
+            ConMsg::Library(msg) => self.library.update(msg, &self.ctrl),
+            ConMsg::Files(msg) => self.files.update(msg, &self.ctrl),
+            // the rows shown may have changed
+            ConMsg::Search(msg) => Task::batch([
+                self.search.update(msg, &self.ctrl),
+                self.request_covers(),
+            ]),
+            ConMsg::Playlists(msg) => Task::batch([
+                self.playlists.update(msg, &self.ctrl),
+                self.request_covers(),
+            ]),
+            ConMsg::Outputs(msg) => self.outputs.update(msg),
+            ConMsg::Partitions(msg) => self.partitions.update(msg),
+            ConMsg::Stats(msg) => self.stats.update(msg, &self.ctrl),
+
+            ConMsg::UpdateAudio(audio) => {
+                self.details.update_audio(audio);
+                Task::none()
+            }
         }
     }
 
-    pub fn view(&self) -> Element<ConMsg> {
-        self.player.view().map(ConMsg::Cmd)
+    pub fn view(&self) -> Element<'_, ConMsg> {
+        let panel = match self.panel {
+            Panel::Player => self.player
+                .view(
+                    &self.permissions,
+                    &self.formats.now_playing,
+                    self.player
+                        .get_song_info()
+                        .and_then(|info| self.covers.get(&info.cover_key())),
+                )
+                .map(ConMsg::Cmd),
+
+            Panel::Queue => self.queue
+                .view(self.player.get_current_id(), &self.permissions, &self.formats.queue)
+                .map(ConMsg::Queue),
+
+            Panel::Library => self.library
+                .view(&self.permissions)
+                .map(ConMsg::Library),
+
+            Panel::Files => self.files
+                .view(&self.permissions)
+                .map(ConMsg::Files),
+
+            Panel::Search => self.search
+                .view(&self.permissions)
+                .map(ConMsg::Search),
+
+            Panel::Playlists => {
+                let current = self.player
+                    .get_current_id()
+                    .and_then(|id| self.queue.get(&id))
+                    .map(|info| info.get_url());
+
+                self.playlists
+                    .view(current, &self.permissions)
+                    .map(ConMsg::Playlists)
+            }
+
+            Panel::Outputs => self.outputs
+                .view(&self.permissions)
+                .map(ConMsg::Outputs),
+
+            Panel::Partitions => self.partitions
+                .view(self.player.get_partition(), &self.permissions)
+                .map(ConMsg::Partitions),
+
+            Panel::Stats => self.stats
+                .view()
+                .map(ConMsg::Stats),
+
+            Panel::Details => self.details
+                .view(self.player.get_song_info()),
+        };
+
+        let content = widget::Column::new()
+            .push(self.view_tabs())
+            .push(panel)
+            .into();
+
+        self.notifications.view(content, ConMsg::Dismiss)
+    }
+
+    fn view_tabs(&self) -> Element<'_, ConMsg> {
+        let tabs = Panel::ALL.iter().map(|&panel| {
+            let active = panel == self.panel;
+            widget::button(widget::text(panel.label()).size(14))
+                .style(move |theme, status| tab_style(theme, status, active))
+                .on_press(ConMsg::ShowPanel(panel))
+                .into()
+        });
+
+        let action = |label, cmd: Cmd| widget::button(widget::text(label).size(14))
+            .style(widget::button::text)
+            .on_press_maybe(self.permissions
+                .allows(cmd.mpd_command())
+                .then_some(ConMsg::Cmd(cmd)));
+
+        let database = match self.player.get_update_job() {
+            Some(job) => widget::Row::new()
+                .push(widget::text(format!("updating database (job {job})…")).size(14)),
+
+            None => widget::Row::new()
+                .spacing(2)
+                .push(action("Update", Cmd::Update(None)))
+                .push(action("Rescan", Cmd::Rescan(None))),
+        };
+
+        widget::Row::with_children(tabs)
+            .spacing(2)
+            .padding([5, 10])
+            .align_y(iced::Center)
+            .push(widget::horizontal_space())
+            .push(database)
+            .into()
+    }
+
+    fn notify(&mut self, text: impl Into<String>) -> Task<Result<ConMsg, Error>> {
+        self.notifications
+            .push(text)
+            .map(|id| Ok(ConMsg::Dismiss(id)))
+    }
+
+    fn notify_error(&mut self, text: impl Into<String>) -> Task<Result<ConMsg, Error>> {
+        self.notifications
+            .push_error(text)
+            .map(|id| Ok(ConMsg::Dismiss(id)))
+    }
+
+    pub fn show_error(&mut self, error: Error) -> Task<Result<ConMsg, Error>> {
+        tracing::warn!("request failed: {error}");
+        self.notify_error(describe(&error))
+    }
+
+    fn update_status(&mut self, status: Status) -> Task<Result<ConMsg, Error>> {
+        self.player.update_status(status);
+
+        if self.player.get_update_job().is_some() {
+            self.updating = true;
+            Task::none()
+        } else if self.updating {
+            self.updating = false;
+            self.notify("Database update finished")
+        } else {
+            Task::none()
+        }
+    }
+
+    pub fn request_permissions(&self) -> Task<Result<ConMsg, Error>> {
+        let cc = self.ctrl.clone();
+        Task::perform(
+            async move { cc.get_permissions().await },
+            |result| match result {
+                Ok(permissions) => Ok(ConMsg::UpdatePermissions(permissions)),
+                Err(error) => Err(error),
+            }
+        )
     }
 
     pub fn request_queue(&self) -> Task<Result<ConMsg, Error>> {
@@ -197,6 +602,28 @@ impl Connected {
         )
     }
 
+    pub fn request_modes(&self) -> Task<Result<ConMsg, Error>> {
+        let cc = self.ctrl.clone();
+        Task::perform(
+            async move { cc.get_modes().await },
+            |result| match result {
+                Ok((mixramp, replay_gain)) => Ok(ConMsg::UpdateModes(mixramp, replay_gain)),
+                Err(error) => Err(error),
+            }
+        )
+    }
+
+    fn request_audio_status(&self) -> Task<Result<ConMsg, Error>> {
+        let cc = self.ctrl.clone();
+        Task::perform(
+            async move { cc.get_audio_status().await },
+            |result| match result {
+                Ok(audio) => Ok(ConMsg::UpdateAudio(audio)),
+                Err(error) => Err(error),
+            }
+        )
+    }
+
     fn request_song_info(&self) -> Task<Result<ConMsg, Error>> {
         let cc = self.ctrl.clone();
         Task::perform(
@@ -219,38 +646,97 @@ impl Connected {
         )
     }
 
-    fn request_cover_art(&self, id: SongId) -> Task<Result<ConMsg, Error>> {
-        let Some(info) = self.queue.get(&id) else {
-            tracing::warn!("requested cover artwork for unqueued song {}", id.0);
-            return Task::none();
-        };
+    // download missing cover art: of the current song first, then of
+    // the upcoming songs of the queue and of the rows shown in the panel.
+    //
+    // At most `prefetch_concurrency` downloads run at once, those no
+    // longer among the most wanted ones are cancelled. All these covers
+    // are pinned in the store, so fetching one cannot evict another.
+    fn request_covers(&mut self) -> Task<Result<ConMsg, Error>> {
+        let current = self.player.get_current_id();
+        let songs: Vec<_> = current
+            .into_iter()
+            .chain(self.player.get_next_id())
+            .filter_map(|id| self.queue.get(&id))
+            .chain(self.queue.upcoming(current, self.prefetch))
+            .chain(self.visible_songs())
+            .map(|info| (info.cover_key(), info.get_url().to_owned()))
+            .collect();
+
+        let mut pinned = HashSet::new();
+        let mut wanted: Vec<(String, String)> = Vec::new();
+        for (key, url) in songs {
+            if !pinned.insert(key.clone()) {
+                continue;
+            }
 
-        if !info.is_cover_missing() {
-            return Task::none();
+            if !self.covers.contains(&key) && wanted.len() < self.prefetch_concurrency {
+                wanted.push((key, url));
+            }
         }
 
-        let url = info.get_url().to_owned();
-        tracing::debug!("requesting cover art for {}: {url}", id.0);
+        self.covers.pin(pinned);
+        self.cover_requests.retain(|key, _| wanted.iter().any(|(x, _)| x == key));
+
+        let tasks: Vec<_> = wanted
+            .into_iter()
+            .filter(|(key, _)| !self.cover_requests.contains_key(key))
+            .collect();
+
+        Task::batch(tasks
+            .into_iter()
+            .map(|(key, url)| self.request_cover_art(key, url)))
+    }
+
+    fn visible_songs(&self) -> Vec<&SongInfo> {
+        match self.panel {
+            Panel::Queue => self.queue.visible_songs(),
+            Panel::Search => self.search.visible_songs(),
+            Panel::Playlists => self.playlists.visible_songs(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn request_cover_art(&mut self, cover_key: String, url: String) -> Task<Result<ConMsg, Error>> {
+        tracing::debug!("requesting cover art of {cover_key}: {url}");
 
         let cc = self.ctrl.clone();
-        Task::perform(
-            async move { cc.get_cover_art(&url).await },
+        let cache = self.cover_cache.clone();
+        let lookup = self.cover_lookup.clone();
+        let key = cover_key.clone();
+        let (task, handle) = Task::perform(
+            async move {
+                if let Some(art) = cache.load(&key).await {
+                    return ConMsg::UpdateCoverArt(key, art);
+                }
 
-            move |result| match result {
-                Ok(art) => Ok(ConMsg::UpdateCoverArt(id, art)),
+                match lookup.fetch(&cc, &url).await {
+                    Ok(art) => {
+                        cache.store(&key, art.as_ref()).await;
+                        ConMsg::UpdateCoverArt(key, art)
+                    }
 
-                // Handle "File Not Found" (code 50) response as "No Artwork"
-                Err(Error::MpdErrorResponse(50))
-                    => Ok(ConMsg::UpdateCoverArt(id, None)),
+                    // the error still goes up once the download is forgotten
+                    Err(error) if error.kind() == ErrorKind::Retryable
+                        => ConMsg::CoverArtFailed(key, error),
 
-                // Escalate other errors
-                Err(error) => Err(error),
-            }
-        )
+                    // don't interrupt with toasts for covers in the background,
+                    // and don't try again in this session
+                    Err(error) => {
+                        tracing::warn!("cover art of {url}: {error}");
+                        ConMsg::UpdateCoverArt(key, None)
+                    }
+                }
+            },
+
+            Ok,
+        ).abortable();
+
+        self.cover_requests.insert(cover_key, handle.abort_on_drop());
+        task
     }
 
     fn toggle(&mut self, toggle: Toggle) -> Task<Result<ConMsg, Error>> {
-        let cc = self.ctrl.clone();
         let cmd = match toggle {
             Toggle::ShowOptions => {
                 self.player.toggle_show_options();
@@ -269,46 +755,44 @@ impl Connected {
                 None
             }
 
-            Toggle::Random => {
-                self.player
-                    .get_random()
-                    .map(|flag| Task::perform(
-                        async move { cc.command(Cmd::SetRandom(!flag)).await },
-                        |result| Ok(ConMsg::CmdResult(result)),
-                    ))
-            }
+            Toggle::Random => self.player
+                .get_random()
+                .map(|flag| Cmd::SetRandom(!flag)),
 
-            Toggle::Loop => {
-                self.player
-                    .get_loop()
-                    .map(|flag| Task::perform(
-                        async move { cc.command(Cmd::SetRepeat(!flag)).await },
-                        |result| Ok(ConMsg::CmdResult(result)),
-                    ))
-            }
+            Toggle::Loop => self.player
+                .get_loop()
+                .map(|flag| Cmd::SetRepeat(!flag)),
 
-            Toggle::Consume => {
-                self.player
-                    .get_consume()
-                    .map(|flag| Task::perform(
-                        async move { cc.command(Cmd::SetConsume(!flag)).await },
-                        |result| Ok(ConMsg::CmdResult(result)),
-                    ))
-            }
+            Toggle::Consume => self.player
+                .get_consume()
+                .map(|flag| Cmd::SetConsume(!flag)),
 
-            Toggle::Play => {
-                let cmd = if self.player.is_playing() {
-                    Cmd::Pause
-                } else {
-                    Cmd::Play
-                };
-                Some(Task::perform(
-                    async move { cc.command(cmd).await },
-                    |result| Ok(ConMsg::CmdResult(result)),
-                ))
-            }
+            Toggle::Single => self.player.next_single(),
+            Toggle::ReplayGain => self.player.next_replay_gain(),
+
+            Toggle::Play => if self.player.is_playing() {
+                Some(Cmd::Pause)
+            } else {
+                Some(Cmd::Play)
+            },
         };
 
-        cmd.unwrap_or(Task::none())
+        cmd.map(|cmd| self.update(ConMsg::Cmd(cmd)))
+            .unwrap_or(Task::none())
+    }
+}
+
+fn tab_style(theme: &Theme, status: widget::button::Status, active: bool) -> widget::button::Style {
+    if active {
+        widget::button::primary(theme, status)
+    } else {
+        widget::button::text(theme, status)
+    }
+}
+
+fn describe(error: &Error) -> String {
+    match error.kind() {
+        ErrorKind::Permission => format!("{error} (a password may allow it)"),
+        _ => error.to_string(),
     }
 }
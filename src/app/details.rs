@@ -0,0 +1,104 @@
+use std::time::{Duration, Instant};
+use iced::{widget, Element};
+
+use crate::mpd::AudioStatus;
+use super::song_info::{SongInfo, tag_name};
+
+#[derive(Default)]
+pub struct Details {
+    audio: AudioStatus,
+    updated: Option<Instant>,
+}
+
+impl Details {
+    const INTERVAL: Duration = Duration::from_secs(1);
+
+    pub fn update_audio(&mut self, audio: AudioStatus) {
+        self.audio = audio;
+    }
+
+    pub fn is_due(&mut self) -> bool {
+        let now = Instant::now();
+        if self.updated.is_some_and(|time| now - time < Self::INTERVAL) {
+            return false;
+        }
+        self.updated = Some(now);
+        true
+    }
+
+    pub fn view<'a, M: 'a>(&'a self, info: Option<&'a SongInfo>) -> Element<'a, M> {
+        use iced::{font, Fill, Font};
+
+        let Some(info) = info else {
+            return widget::container(widget::text("Nothing playing").size(14))
+                .padding(10)
+                .into();
+        };
+
+        let row = |name: String, values: Vec<String>| widget::Row::new()
+            .spacing(10)
+            .push(widget::text(name)
+                .size(14)
+                .width(220)
+                .font(Font { weight: font::Weight::Bold, ..Font::default() }))
+            // one line per value of multi-valued tags
+            .push(widget::Column::with_children(values
+                .into_iter()
+                .map(|value| widget::text(value).size(14).into())))
+            .into();
+
+        let tags = info.tags
+            .iter()
+            .map(|(tag, values)| row(tag_name(tag), values.clone()));
+
+        let file = [
+            ("Duration", Some(info.duration_str()).filter(|d| !d.is_empty())),
+            ("File", Some(info.get_url().to_owned())),
+            ("File format", info.format.as_deref().map(format_audio)),
+            ("Audio format", self.audio.format.as_deref().map(format_audio)),
+            ("Bitrate", self.audio.bitrate.map(|rate| format!("{rate} kbit/s"))),
+        ];
+
+        let file = file.into_iter()
+            .filter_map(|(name, value)| value.map(|value| row(name.to_owned(), vec![value])));
+
+        let content = widget::Column::new()
+            .spacing(6)
+            .padding(10)
+            .extend(tags)
+            .push(widget::vertical_space().height(10))
+            .extend(file);
+
+        widget::scrollable(content)
+            .width(Fill)
+            .height(Fill)
+            .into()
+    }
+}
+
+fn format_audio(format: &str) -> String {
+    let parts: Vec<_> = format.split(':').collect();
+    let [rate, bits, channels] = parts[..] else {
+        return format.to_owned();
+    };
+
+    let rate = match rate.parse::<f32>() {
+        Ok(rate) => format!("{} kHz", rate / 1000.0),
+        // DSD rates, like "dsd64"
+        Err(_) => rate.to_owned(),
+    };
+
+    let bits = match bits {
+        "f" => String::from("float"),
+        "dsd" => String::from("DSD"),
+        bits => format!("{bits} bit"),
+    };
+
+    let channels = match channels {
+        "1" => String::from("mono"),
+        "2" => String::from("stereo"),
+        n => format!("{n} channels"),
+    };
+
+    format!("{rate}, {bits}, {channels}")
+}
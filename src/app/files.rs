@@ -0,0 +1,212 @@
+use std::time::{Duration, Instant};
+use iced::{
+    widget::{self, scrollable::Viewport},
+    Element,
+    Task,
+};
+
+use crate::error::Error;
+use crate::mpd::{MpdCtrl, Cmd, AddMode, DirEntry, Permissions};
+use super::connected::ConMsg;
+use super::list::{ListScroll, selectable_row};
+
+#[derive(Debug, Clone)]
+pub enum FilesMsg {
+    Open(String),
+    Entries(String, Vec<DirEntry>),
+    Press(usize),
+    Add(AddMode),
+    Update,
+    Rescan,
+    Scroll(Viewport),
+}
+
+pub struct Files {
+    loaded: bool,
+    path: String,
+    entries: Vec<DirEntry>,
+    selected: Option<usize>,
+    last_click: Option<(usize, Instant)>,
+    scroll: ListScroll,
+}
+
+impl Files {
+    const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+    pub fn new() -> Self {
+        Self {
+            loaded: false,
+            path: String::new(),
+            entries: Vec::new(),
+            selected: None,
+            last_click: None,
+            scroll: ListScroll::default(),
+        }
+    }
+
+    pub fn load(&mut self, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        if self.loaded {
+            return Task::none();
+        }
+        self.loaded = true;
+        self.request_entries(ctrl, self.path.clone())
+    }
+
+    pub fn update(&mut self, msg: FilesMsg, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        match msg {
+            FilesMsg::Open(path) => self.request_entries(ctrl, path),
+
+            FilesMsg::Entries(path, entries) => {
+                self.path = path;
+                self.entries = entries;
+                self.selected = None;
+                self.last_click = None;
+                Task::none()
+            }
+
+            FilesMsg::Press(index) => {
+                let now = Instant::now();
+                let double = matches!(self.last_click,
+                    Some((last, time)) if last == index && now - time < Self::DOUBLE_CLICK);
+
+                self.selected = Some(index);
+                if !double {
+                    self.last_click = Some((index, now));
+                    return Task::none();
+                }
+                self.last_click = None;
+
+                // double-click descends into directories and adds songs
+                match self.entries.get(index) {
+                    Some(DirEntry::Directory(path)) => self.request_entries(ctrl, path.clone()),
+                    Some(DirEntry::Song { file, .. }) => Task::done(Ok(ConMsg::Cmd(
+                        Cmd::Add(vec![file.clone()], AddMode::Append)))),
+                    _ => Task::none(),
+                }
+            }
+
+            FilesMsg::Add(mode) => {
+                // the selection, otherwise the whole directory (recursively)
+                let uri = match self.selected.and_then(|i| self.entries.get(i)) {
+                    Some(DirEntry::Playlist(_)) => return Task::none(),
+                    Some(entry) => entry.path().to_owned(),
+                    None if self.path.is_empty() => String::from("/"),
+                    None => self.path.clone(),
+                };
+                Task::done(Ok(ConMsg::Cmd(Cmd::Add(vec![uri], mode))))
+            }
+
+            FilesMsg::Update => {
+                let cmd = Cmd::Update(self.update_path());
+                Task::done(Ok(ConMsg::Cmd(cmd)))
+            }
+
+            FilesMsg::Rescan => {
+                let cmd = Cmd::Rescan(self.update_path());
+                Task::done(Ok(ConMsg::Cmd(cmd)))
+            }
+
+            FilesMsg::Scroll(viewport) => {
+                self.scroll.update(viewport);
+                Task::none()
+            }
+        }
+    }
+
+    pub fn refresh(&self, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        if !self.loaded {
+            return Task::none();
+        }
+        self.request_entries(ctrl, self.path.clone())
+    }
+
+    fn update_path(&self) -> Option<String> {
+        match self.selected.and_then(|i| self.entries.get(i)) {
+            Some(DirEntry::Directory(path)) => Some(path.clone()),
+            _ if self.path.is_empty() => None,
+            _ => Some(self.path.clone()),
+        }
+    }
+
+    fn request_entries(&self, ctrl: &MpdCtrl, path: String) -> Task<Result<ConMsg, Error>> {
+        let cc = ctrl.clone();
+        Task::perform(
+            async move {
+                let entries = cc.list_dir(&path).await?;
+                Ok((path, entries))
+            },
+            |result| result
+                .map(|(path, entries)| ConMsg::Files(FilesMsg::Entries(path, entries))),
+        )
+    }
+
+    pub fn view(&self, permissions: &Permissions) -> Element<'_, FilesMsg> {
+        use iced::{Center, Fill};
+
+        let selected = self.selected.and_then(|i| self.entries.get(i));
+        let can_add = permissions.allows("add")
+            && !matches!(selected, Some(DirEntry::Playlist(_)));
+
+        let action = |label, msg: Option<FilesMsg>| widget::button(widget::text(label).size(14))
+            .style(widget::button::secondary)
+            .on_press_maybe(msg);
+        let add = |label, mode| action(label, can_add.then_some(FilesMsg::Add(mode)));
+
+        let toolbar = widget::Row::new()
+            .spacing(5)
+            .align_y(Center)
+            .push(self.view_breadcrumbs())
+            .push(widget::horizontal_space().width(Fill))
+            .push(add("Add", AddMode::Append))
+            .push(add("Insert", AddMode::AfterCurrent))
+            .push(add("Replace", AddMode::Replace))
+            .push(action("Update", permissions
+                .allows("update")
+                .then_some(FilesMsg::Update)))
+            .push(action("Rescan", permissions
+                .allows("rescan")
+                .then_some(FilesMsg::Rescan)));
+
+        let row = |index: usize| {
+            let label = match &self.entries[index] {
+                DirEntry::Directory(_) => format!("{}/", self.entries[index].name()),
+                DirEntry::Playlist(_) => format!("{} (playlist)", self.entries[index].name()),
+                DirEntry::Song { file, title, artist, .. } => match (artist, title) {
+                    (Some(artist), Some(title)) => format!("{artist} - {title}"),
+                    (None, Some(title)) => title.clone(),
+                    _ => file.rsplit('/').next().unwrap_or(file).to_owned(),
+                },
+            };
+            selectable_row(label, self.selected == Some(index), FilesMsg::Press(index))
+        };
+
+        widget::Column::new()
+            .spacing(10)
+            .padding(10)
+            .push(toolbar)
+            .push(self.scroll.view(self.entries.len(), row, FilesMsg::Scroll))
+            .into()
+    }
+
+    fn view_breadcrumbs(&self) -> Element<'_, FilesMsg> {
+        let crumb = |label: &str, path: &str| widget::button(widget::text(label.to_owned()).size(14))
+            .style(widget::button::text)
+            .padding([5, 2])
+            .on_press(FilesMsg::Open(path.to_owned()));
+
+        let mut row = widget::Row::new()
+            .align_y(iced::Center)
+            .push(crumb("Music", ""));
+
+        let mut end = 0;
+        for part in self.path.split('/').filter(|_| !self.path.is_empty()) {
+            end += part.len();
+            row = row
+                .push(widget::text("/").size(14))
+                .push(crumb(part, &self.path[..end]));
+            end += 1;
+        }
+
+        row.into()
+    }
+}
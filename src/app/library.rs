@@ -0,0 +1,336 @@
+use std::fmt;
+use iced::{
+    widget::{self, scrollable::Viewport},
+    Element,
+    Task,
+};
+use mpd_client::{
+    filter::Filter,
+    responses::Song,
+    tag::Tag,
+};
+
+use crate::error::Error;
+use crate::mpd::{MpdCtrl, Cmd, AddMode, Permissions};
+use super::connected::ConMsg;
+use super::list::{ListScroll, selectable_row};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootTag {
+    Artist,
+    AlbumArtist,
+    Genre,
+}
+
+impl RootTag {
+    const ALL: [RootTag; 3] = [
+        RootTag::Artist,
+        RootTag::AlbumArtist,
+        RootTag::Genre,
+    ];
+
+    fn tag(&self) -> Tag {
+        match self {
+            RootTag::Artist => Tag::Artist,
+            RootTag::AlbumArtist => Tag::AlbumArtist,
+            RootTag::Genre => Tag::Genre,
+        }
+    }
+}
+
+impl fmt::Display for RootTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RootTag::Artist => write!(f, "Artist"),
+            RootTag::AlbumArtist => write!(f, "Album artist"),
+            RootTag::Genre => write!(f, "Genre"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum LibraryMsg {
+    SetRoot(RootTag),
+    Values(RootTag, Vec<String>),
+    SelectValue(String),
+    Albums(String, Vec<String>),
+    SelectAlbum(String),
+    Tracks(Option<String>, Option<String>, Vec<Song>),
+    SelectTrack(usize),
+    Add(AddMode),
+    Scroll(usize, Viewport),
+}
+
+pub struct Library {
+    root: RootTag,
+    loaded: bool,
+    values: Vec<String>,
+    value: Option<String>,
+    albums: Vec<String>,
+    album: Option<String>,
+    tracks: Vec<Song>,
+    track: Option<usize>,
+    scroll: [ListScroll; 3],
+}
+
+impl Library {
+    pub fn new() -> Self {
+        Self {
+            root: RootTag::Artist,
+            loaded: false,
+            values: Vec::new(),
+            value: None,
+            albums: Vec::new(),
+            album: None,
+            tracks: Vec::new(),
+            track: None,
+            scroll: Default::default(),
+        }
+    }
+
+    pub fn load(&mut self, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        if self.loaded {
+            return Task::none();
+        }
+        self.loaded = true;
+        self.request_values(ctrl)
+    }
+
+    pub fn update(&mut self, msg: LibraryMsg, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        match msg {
+            LibraryMsg::SetRoot(root) => {
+                self.root = root;
+                self.value = None;
+                self.request_values(ctrl)
+            }
+
+            LibraryMsg::Values(root, values) => {
+                if root == self.root {
+                    self.values = values;
+
+                    // keep the selection, if it still exists
+                    if self.value.as_ref().is_none_or(|value| !self.values.contains(value)) {
+                        self.value = None;
+                        self.albums.clear();
+                        self.album = None;
+                        self.tracks.clear();
+                        self.track = None;
+                    }
+                }
+                Task::none()
+            }
+
+            LibraryMsg::SelectValue(value) => {
+                self.value = Some(value.clone());
+                self.album = None;
+                self.track = None;
+
+                let filter = self.filter();
+                let albums = {
+                    let cc = ctrl.clone();
+                    let filter = filter.clone();
+                    Task::perform(
+                        async move { cc.list(Tag::Album, Some(filter)).await },
+                        move |result| result
+                            .map(|albums| ConMsg::Library(
+                                LibraryMsg::Albums(value.clone(), albums))),
+                    )
+                };
+
+                Task::batch([albums, self.request_tracks(ctrl, filter)])
+            }
+
+            LibraryMsg::Albums(value, albums) => {
+                if self.value.as_ref() == Some(&value) {
+                    self.albums = albums;
+                    if self.album.as_ref().is_some_and(|album| !self.albums.contains(album)) {
+                        self.album = None;
+                    }
+                }
+                Task::none()
+            }
+
+            LibraryMsg::SelectAlbum(album) => {
+                self.album = Some(album);
+                self.track = None;
+                self.request_tracks(ctrl, self.filter())
+            }
+
+            LibraryMsg::Tracks(value, album, mut tracks) => {
+                if value == self.value && album == self.album {
+                    tracks.sort_by(|a, b| a.album()
+                        .cmp(&b.album())
+                        .then(a.number().cmp(&b.number())));
+                    self.tracks = tracks;
+                    self.track = None;
+                }
+                Task::none()
+            }
+
+            LibraryMsg::SelectTrack(index) => {
+                self.track = Some(index);
+                Task::none()
+            }
+
+            LibraryMsg::Add(mode) => {
+                // a selected track, otherwise everything listed
+                let uris: Vec<_> = match self.track.and_then(|i| self.tracks.get(i)) {
+                    Some(song) => vec![song.url.clone()],
+                    None => self.tracks.iter().map(|song| song.url.clone()).collect(),
+                };
+
+                if uris.is_empty() {
+                    Task::none()
+                } else {
+                    Task::done(Ok(ConMsg::Cmd(Cmd::Add(uris, mode))))
+                }
+            }
+
+            LibraryMsg::Scroll(column, viewport) => {
+                self.scroll[column].update(viewport);
+                Task::none()
+            }
+        }
+    }
+
+    pub fn refresh(&self, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        if !self.loaded {
+            return Task::none();
+        }
+
+        let values = self.request_values(ctrl);
+        let Some(value) = self.value.clone() else {
+            return values;
+        };
+
+        let filter = Filter::tag(self.root.tag(), value.clone());
+        let cc = ctrl.clone();
+        let albums = Task::perform(
+            async move { cc.list(Tag::Album, Some(filter)).await },
+            move |result| result
+                .map(|albums| ConMsg::Library(
+                    LibraryMsg::Albums(value.clone(), albums))),
+        );
+
+        Task::batch([values, albums, self.request_tracks(ctrl, self.filter())])
+    }
+
+    fn filter(&self) -> Filter {
+        let value = self.value.clone().unwrap_or_default();
+        let filter = Filter::tag(self.root.tag(), value);
+
+        match &self.album {
+            Some(album) => filter.and(Filter::tag(Tag::Album, album.clone())),
+            None => filter,
+        }
+    }
+
+    fn request_values(&self, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        let root = self.root;
+        let cc = ctrl.clone();
+        Task::perform(
+            async move { cc.list(root.tag(), None).await },
+            move |result| result
+                .map(|values| ConMsg::Library(LibraryMsg::Values(root, values))),
+        )
+    }
+
+    fn request_tracks(&self, ctrl: &MpdCtrl, filter: Filter) -> Task<Result<ConMsg, Error>> {
+        let value = self.value.clone();
+        let album = self.album.clone();
+        let cc = ctrl.clone();
+        Task::perform(
+            async move { cc.find(filter).await },
+            move |result| result
+                .map(|tracks| ConMsg::Library(
+                    LibraryMsg::Tracks(value.clone(), album.clone(), tracks))),
+        )
+    }
+
+    pub fn view(&self, permissions: &Permissions) -> Element<'_, LibraryMsg> {
+        use iced::{Center, Fill, FillPortion};
+
+        let can_add = permissions.allows("add") && !self.tracks.is_empty();
+        let action = |label, mode| widget::button(widget::text(label).size(14))
+            .style(widget::button::secondary)
+            .on_press_maybe(can_add.then_some(LibraryMsg::Add(mode)));
+
+        let toolbar = widget::Row::new()
+            .spacing(5)
+            .align_y(Center)
+            .push(widget::pick_list(RootTag::ALL, Some(self.root), LibraryMsg::SetRoot)
+                .text_size(14))
+            .push(widget::horizontal_space().width(Fill))
+            .push(action("Add", AddMode::Append))
+            .push(action("Insert", AddMode::AfterCurrent))
+            .push(action("Replace", AddMode::Replace));
+
+        let values = self.scroll[0].view(
+            self.values.len(),
+            |i| {
+                let value = &self.values[i];
+                selectable_row(
+                    display_value(value),
+                    self.value.as_ref() == Some(value),
+                    LibraryMsg::SelectValue(value.clone()),
+                )
+            },
+            |viewport| LibraryMsg::Scroll(0, viewport),
+        );
+
+        let albums = self.scroll[1].view(
+            self.albums.len(),
+            |i| {
+                let album = &self.albums[i];
+                selectable_row(
+                    display_value(album),
+                    self.album.as_ref() == Some(album),
+                    LibraryMsg::SelectAlbum(album.clone()),
+                )
+            },
+            |viewport| LibraryMsg::Scroll(1, viewport),
+        );
+
+        let tracks = self.scroll[2].view(
+            self.tracks.len(),
+            |i| {
+                let song = &self.tracks[i];
+                let title = song.title().unwrap_or(&song.url);
+                let label = match song.number() {
+                    (_, 0) => title.to_owned(),
+                    (_, track) => format!("{track:>2}. {title}"),
+                };
+                selectable_row(label, self.track == Some(i), LibraryMsg::SelectTrack(i))
+            },
+            |viewport| LibraryMsg::Scroll(2, viewport),
+        );
+
+        let column = |title, list| widget::Column::new()
+            .spacing(5)
+            .width(FillPortion(1))
+            .push(widget::text(title).size(14))
+            .push(list);
+
+        let album_column = column("Album".to_owned(), albums);
+        let columns = widget::Row::new()
+            .spacing(10)
+            .push(column(self.root.to_string(), values))
+            .push(album_column)
+            .push(column("Tracks".to_owned(), tracks).width(FillPortion(2)));
+
+        widget::Column::new()
+            .spacing(10)
+            .padding(10)
+            .push(toolbar)
+            .push(columns)
+            .into()
+    }
+}
+
+fn display_value(value: &str) -> String {
+    if value.is_empty() {
+        String::from("(none)")
+    } else {
+        value.to_owned()
+    }
+}
@@ -0,0 +1,94 @@
+use std::ops::Range;
+use iced::{
+    widget::{self, scrollable::Viewport},
+    Element,
+    Fill,
+    Theme,
+};
+
+// scroll position of a list with rows of a fixed height.
+//
+// Long lists (like a queue with thousands of songs) are expensive to
+// lay out, so only the rows inside the viewport are built and the rest
+// is replaced by empty space.
+#[derive(Debug, Clone, Copy)]
+pub struct ListScroll {
+    offset: f32,
+    height: f32,
+}
+
+impl Default for ListScroll {
+    fn default() -> Self {
+        // until the scrollable reports its size, assume a large window
+        Self { offset: 0.0, height: 1200.0 }
+    }
+}
+
+impl ListScroll {
+    pub const ROW_HEIGHT: f32 = 28.0;
+
+    pub fn update(&mut self, viewport: Viewport) {
+        self.offset = viewport.absolute_offset().y;
+        self.height = viewport.bounds().height;
+    }
+
+    pub fn visible(&self, len: usize) -> Range<usize> {
+        let first = (self.offset / Self::ROW_HEIGHT).floor() as usize;
+        let count = (self.height / Self::ROW_HEIGHT).ceil() as usize + 1;
+        first.min(len)..(first + count).min(len)
+    }
+
+    pub fn view<'a, M: 'a>(
+        &self,
+        len: usize,
+        row: impl Fn(usize) -> Element<'a, M>,
+        on_scroll: impl Fn(Viewport) -> M + 'a,
+    ) -> Element<'a, M> {
+        let visible = self.visible(len);
+        let above = visible.start as f32 * Self::ROW_HEIGHT;
+        let below = (len - visible.end) as f32 * Self::ROW_HEIGHT;
+
+        let rows = widget::Column::new()
+            .width(Fill)
+            .push(widget::vertical_space().height(above))
+            .extend(visible.map(|i| widget::container(row(i))
+                .height(Self::ROW_HEIGHT)
+                .into()))
+            .push(widget::vertical_space().height(below));
+
+        widget::scrollable(rows)
+            .on_scroll(on_scroll)
+            .height(Fill)
+            .into()
+    }
+}
+
+pub fn selectable_row<'a, M: Clone + 'a>(
+    label: impl widget::text::IntoFragment<'a>,
+    selected: bool,
+    on_press: M,
+) -> Element<'a, M> {
+    let text = widget::text(label)
+        .size(14)
+        .wrapping(widget::text::Wrapping::None);
+
+    widget::mouse_area(widget::container(text)
+        .width(Fill)
+        .height(Fill)
+        .padding([0, 5])
+        .align_y(iced::Center)
+        .style(move |theme| selected_style(theme, selected)))
+        .on_press(on_press)
+        .into()
+}
+
+pub fn selected_style(theme: &Theme, selected: bool) -> widget::container::Style {
+    if !selected {
+        return widget::container::Style::default();
+    }
+
+    let pair = theme.extended_palette().secondary.base;
+    widget::container::Style::default()
+        .background(pair.color)
+        .color(pair.text)
+}
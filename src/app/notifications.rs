@@ -0,0 +1,95 @@
+use std::time::Duration;
+use iced::{widget, Element, Task, Theme};
+
+#[derive(Default)]
+pub struct Notifications {
+    next_id: u64,
+    items: Vec<(u64, Kind, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Info,
+    Error,
+}
+
+impl Notifications {
+    const TIMEOUT: Duration = Duration::from_secs(5);
+    const ERROR_TIMEOUT: Duration = Duration::from_secs(10);
+    const MAX_SHOWN: usize = 5;
+
+    pub fn push(&mut self, text: impl Into<String>) -> Task<u64> {
+        self.add(Kind::Info, text.into(), Self::TIMEOUT)
+    }
+
+    pub fn push_error(&mut self, text: impl Into<String>) -> Task<u64> {
+        self.add(Kind::Error, text.into(), Self::ERROR_TIMEOUT)
+    }
+
+    fn add(&mut self, kind: Kind, text: String, timeout: Duration) -> Task<u64> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.items.push((id, kind, text));
+        if self.items.len() > Self::MAX_SHOWN {
+            self.items.remove(0);
+        }
+
+        Task::perform(tokio::time::sleep(timeout), move |_| id)
+    }
+
+    pub fn dismiss(&mut self, id: u64) {
+        self.items.retain(|(x, _, _)| *x != id);
+    }
+
+    pub fn view<'a, M: Clone + 'a>(
+        &'a self,
+        content: Element<'a, M>,
+        on_dismiss: impl Fn(u64) -> M,
+    ) -> Element<'a, M> {
+        use iced::{Bottom, Fill, Right};
+
+        if self.items.is_empty() {
+            return content;
+        }
+
+        let toasts = self.items.iter().map(|(id, kind, text)| {
+            let line = widget::Row::new()
+                .spacing(10)
+                .align_y(iced::Center)
+                .push(widget::text(text).size(14))
+                .push(widget::button(widget::text("×").size(14))
+                    .style(widget::button::text)
+                    .padding([0, 4])
+                    .on_press(on_dismiss(*id)));
+
+            widget::container(line)
+                .padding([6, 12])
+                .style(move |theme| toast_style(theme, *kind))
+                .into()
+        });
+
+        let toasts = widget::container(widget::Column::with_children(toasts)
+            .spacing(5)
+            .align_x(Right))
+            .padding(15)
+            .width(Fill)
+            .height(Fill)
+            .align_x(Right)
+            .align_y(Bottom);
+
+        widget::stack![content, toasts].into()
+    }
+}
+
+fn toast_style(theme: &Theme, kind: Kind) -> widget::container::Style {
+    let pal = theme.extended_palette();
+    let pair = match kind {
+        Kind::Info => pal.background.strong,
+        Kind::Error => pal.danger.base,
+    };
+    widget::container::Style::default()
+        .background(pair.color)
+        .color(pair.text)
+        .border(iced::border::rounded(4))
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use iced::{
+    widget,
+    Element,
+    Task,
+};
+
+use crate::error::Error;
+use crate::mpd::{MpdCtrl, Cmd, Output, Permissions};
+use super::connected::ConMsg;
+
+#[derive(Debug, Clone)]
+pub enum OutputsMsg {
+    Outputs(Vec<Output>),
+    Enable(u32, bool),
+    Toggle(u32),
+    Edit(u32, String, String),
+    Apply(u32, String),
+}
+
+pub struct Outputs {
+    loaded: bool,
+    outputs: Vec<Output>,
+    edits: HashMap<(u32, String), String>,
+}
+
+impl Outputs {
+    pub fn new() -> Self {
+        Self {
+            loaded: false,
+            outputs: Vec::new(),
+            edits: HashMap::new(),
+        }
+    }
+
+    pub fn load(&mut self, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        if self.loaded {
+            return Task::none();
+        }
+        self.loaded = true;
+        self.refresh(ctrl)
+    }
+
+    pub fn refresh(&self, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        if !self.loaded {
+            return Task::none();
+        }
+
+        let cc = ctrl.clone();
+        Task::perform(
+            async move { cc.get_outputs().await },
+            |result| result.map(|outputs| ConMsg::Outputs(OutputsMsg::Outputs(outputs))),
+        )
+    }
+
+    pub fn update(&mut self, msg: OutputsMsg) -> Task<Result<ConMsg, Error>> {
+        let cmd = match msg {
+            OutputsMsg::Outputs(outputs) => {
+                self.outputs = outputs;
+                None
+            }
+
+            OutputsMsg::Enable(id, true) => Some(Cmd::EnableOutput(id)),
+            OutputsMsg::Enable(id, false) => Some(Cmd::DisableOutput(id)),
+            OutputsMsg::Toggle(id) => Some(Cmd::ToggleOutput(id)),
+
+            OutputsMsg::Edit(id, name, value) => {
+                self.edits.insert((id, name), value);
+                None
+            }
+
+            OutputsMsg::Apply(id, name) => self.edits
+                .remove(&(id, name.clone()))
+                .map(|value| Cmd::SetOutputAttribute(id, name, value)),
+        };
+
+        match cmd {
+            Some(cmd) => Task::done(Ok(ConMsg::Cmd(cmd))),
+            None => Task::none(),
+        }
+    }
+
+    pub fn view(&self, permissions: &Permissions) -> Element<'_, OutputsMsg> {
+        use iced::{font, Center, Fill, Font};
+
+        let can_enable = permissions.allows("enableoutput") && permissions.allows("disableoutput");
+        let can_toggle = permissions.allows("toggleoutput");
+        let can_set = permissions.allows("outputset");
+
+        let outputs = self.outputs.iter().map(|output| {
+            let id = output.id;
+
+            let header = widget::Row::new()
+                .spacing(10)
+                .align_y(Center)
+                .push(widget::toggler(output.enabled)
+                    .on_toggle_maybe(can_enable.then_some(move |on| OutputsMsg::Enable(id, on))))
+                .push(widget::text(&output.name)
+                    .size(16)
+                    .font(Font { weight: font::Weight::Bold, ..Font::default() }))
+                .push(widget::text(format!("({})", output.plugin)).size(14))
+                .push(widget::horizontal_space().width(Fill))
+                .push(widget::button(widget::text("Toggle").size(14))
+                    .style(widget::button::secondary)
+                    .on_press_maybe(can_toggle.then_some(OutputsMsg::Toggle(id))));
+
+            let attributes = output.attributes.iter().map(|(name, value)| {
+                let edit = self.edits.get(&(id, name.clone()));
+
+                let mut input = widget::text_input("", edit.unwrap_or(value))
+                    .size(14)
+                    .width(Fill);
+                if can_set {
+                    let key = name.clone();
+                    input = input
+                        .on_input(move |value| OutputsMsg::Edit(id, key.clone(), value))
+                        .on_submit(OutputsMsg::Apply(id, name.clone()));
+                }
+
+                widget::Row::new()
+                    .spacing(10)
+                    .align_y(Center)
+                    .push(widget::text(name).size(14).width(150))
+                    .push(input)
+                    .push(widget::button(widget::text("Set").size(14))
+                        .style(widget::button::secondary)
+                        .on_press_maybe(edit.map(|_| OutputsMsg::Apply(id, name.clone()))))
+                    .into()
+            });
+
+            widget::container(widget::Column::new()
+                .spacing(5)
+                .push(header)
+                .extend(attributes))
+                .padding(10)
+                .style(widget::container::rounded_box)
+                .into()
+        });
+
+        let content = widget::Column::with_children(outputs)
+            .spacing(10)
+            .padding(10);
+
+        widget::scrollable(content)
+            .height(Fill)
+            .into()
+    }
+}
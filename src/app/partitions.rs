@@ -0,0 +1,200 @@
+use iced::{
+    widget::{self, scrollable::Viewport},
+    Element,
+    Task,
+};
+
+use crate::error::Error;
+use crate::mpd::{MpdCtrl, Cmd, Output, Permissions};
+use super::connected::ConMsg;
+use super::list::{ListScroll, selectable_row};
+
+#[derive(Debug, Clone)]
+pub enum PartitionsMsg {
+    Partitions(Vec<String>),
+    Outputs(Vec<Output>),
+    Select(String),
+    Name(String),
+    Switch,
+    Create,
+    Delete,
+    MoveOutput(String),
+    Scroll(Viewport),
+}
+
+pub struct Partitions {
+    loaded: bool,
+    names: Vec<String>,
+    outputs: Vec<Output>,
+    selected: Option<String>,
+    name: String,
+    scroll: ListScroll,
+}
+
+impl Partitions {
+    pub fn new() -> Self {
+        Self {
+            loaded: false,
+            names: Vec::new(),
+            outputs: Vec::new(),
+            selected: None,
+            name: String::new(),
+            scroll: ListScroll::default(),
+        }
+    }
+
+    pub fn load(&mut self, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        if self.loaded {
+            return Task::none();
+        }
+        self.loaded = true;
+        self.refresh(ctrl)
+    }
+
+    pub fn refresh(&self, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        if !self.loaded {
+            return Task::none();
+        }
+
+        let cc = ctrl.clone();
+        let partitions = Task::perform(
+            async move { cc.get_partitions().await },
+            |result| result.map(|names| ConMsg::Partitions(PartitionsMsg::Partitions(names))),
+        );
+
+        let cc = ctrl.clone();
+        let outputs = Task::perform(
+            async move { cc.get_outputs().await },
+            |result| result.map(|outputs| ConMsg::Partitions(PartitionsMsg::Outputs(outputs))),
+        );
+
+        Task::batch([partitions, outputs])
+    }
+
+    pub fn update(&mut self, msg: PartitionsMsg) -> Task<Result<ConMsg, Error>> {
+        let cmd = match msg {
+            PartitionsMsg::Partitions(names) => {
+                self.names = names;
+                if self.selected.as_ref().is_some_and(|name| !self.names.contains(name)) {
+                    self.selected = None;
+                }
+                None
+            }
+
+            PartitionsMsg::Outputs(outputs) => {
+                self.outputs = outputs;
+                None
+            }
+
+            PartitionsMsg::Select(name) => {
+                self.selected = Some(name);
+                None
+            }
+
+            PartitionsMsg::Name(name) => {
+                self.name = name;
+                None
+            }
+
+            PartitionsMsg::Switch => self.selected
+                .clone()
+                .map(Cmd::SwitchPartition),
+
+            PartitionsMsg::Create => {
+                let name = self.name.trim().to_owned();
+                self.name.clear();
+                (!name.is_empty()).then_some(Cmd::NewPartition(name))
+            }
+
+            PartitionsMsg::Delete => self.selected
+                .clone()
+                .map(Cmd::DeletePartition),
+
+            PartitionsMsg::MoveOutput(name) => Some(Cmd::MoveOutput(name)),
+
+            PartitionsMsg::Scroll(viewport) => {
+                self.scroll.update(viewport);
+                None
+            }
+        };
+
+        match cmd {
+            Some(cmd) => Task::done(Ok(ConMsg::Cmd(cmd))),
+            None => Task::none(),
+        }
+    }
+
+    pub fn view(&self, active: Option<&str>, permissions: &Permissions) -> Element<'_, PartitionsMsg> {
+        use iced::{Center, Fill, FillPortion};
+
+        let action = |label, cmd: &str, msg: Option<PartitionsMsg>| widget::button(widget::text(label).size(14))
+            .style(widget::button::secondary)
+            .on_press_maybe(msg.filter(|_| permissions.allows(cmd)));
+
+        // the active partition can neither be switched to nor deleted
+        let other = self.selected
+            .as_deref()
+            .filter(|name| Some(*name) != active)
+            .is_some();
+
+        let toolbar = widget::Row::new()
+            .spacing(5)
+            .align_y(Center)
+            .push(action("Switch", "partition", other.then_some(PartitionsMsg::Switch)))
+            .push(action("Delete", "delpartition", other.then_some(PartitionsMsg::Delete)))
+            .push(widget::horizontal_space().width(Fill))
+            .push(widget::text_input("Partition name", &self.name)
+                .on_input(PartitionsMsg::Name)
+                .on_submit(PartitionsMsg::Create)
+                .size(14)
+                .width(200))
+            .push(action("Create", "newpartition", Some(PartitionsMsg::Create)
+                .filter(|_| !self.name.trim().is_empty())));
+
+        let partitions = self.scroll.view(
+            self.names.len(),
+            |i| {
+                let name = &self.names[i];
+                let label = if Some(name.as_str()) == active {
+                    format!("{name} (active)")
+                } else {
+                    name.clone()
+                };
+                selectable_row(
+                    label,
+                    self.selected.as_ref() == Some(name),
+                    PartitionsMsg::Select(name.clone()),
+                )
+            },
+            PartitionsMsg::Scroll,
+        );
+
+        let outputs = self.outputs.iter().map(|output| widget::Row::new()
+            .spacing(10)
+            .align_y(Center)
+            .push(widget::text(format!("{} ({})", output.name, output.plugin))
+                .size(14)
+                .width(Fill))
+            .push(action("Move here", "moveoutput",
+                Some(PartitionsMsg::MoveOutput(output.name.clone()))))
+            .into());
+
+        let outputs = widget::Column::new()
+            .spacing(5)
+            .width(FillPortion(1))
+            .push(widget::text("Outputs").size(14))
+            .extend(outputs);
+
+        let columns = widget::Row::new()
+            .spacing(10)
+            .push(widget::container(partitions).width(FillPortion(1)))
+            .push(outputs);
+
+        widget::Column::new()
+            .spacing(10)
+            .padding(10)
+            .push(toolbar)
+            .push(columns)
+            .into()
+    }
+}
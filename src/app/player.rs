@@ -1,16 +1,19 @@
-use std::time::Duration;
+use std::{fmt, time::Duration};
 use lazy_static::lazy_static;
 use iced::{
-    widget::{svg, button},
+    widget::{svg, button, image},
     Element,
     Theme,
 };
 use mpd_client::{
-    commands::SongId,
+    commands::{SongId, SingleMode, ReplayGainMode},
     responses::{Status, PlayState},
 };
 
-use crate::mpd::Cmd;
+use crate::config::Layout;
+use crate::format::Format;
+use crate::mpd::{Cmd, MixRamp, Permissions};
+use super::connected::Adjust;
 use super::song_info::SongInfo;
 use super::progress::Progress;
 
@@ -37,29 +40,58 @@ lazy_static! {
 }
 
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Choice<T> {
+    value: T,
+    label: &'static str,
+}
+
+impl<T> fmt::Display for Choice<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+const SINGLE_MODES: [Choice<SingleMode>; 3] = [
+    Choice { value: SingleMode::Disabled, label: "off" },
+    Choice { value: SingleMode::Enabled, label: "on" },
+    Choice { value: SingleMode::Oneshot, label: "oneshot" },
+];
+
+const REPLAY_GAIN_MODES: [Choice<ReplayGainMode>; 4] = [
+    Choice { value: ReplayGainMode::Off, label: "off" },
+    Choice { value: ReplayGainMode::Track, label: "track" },
+    Choice { value: ReplayGainMode::Album, label: "album" },
+    Choice { value: ReplayGainMode::Auto, label: "auto" },
+];
+
+fn next_choice<T: Copy + PartialEq>(choices: &[Choice<T>], value: T) -> T {
+    let index = choices.iter()
+        .position(|choice| choice.value == value)
+        .unwrap_or(0);
+    choices[(index + 1) % choices.len()].value
+}
+
+
 pub struct Player {
     song_info: Option<SongInfo>,
     progress: Option<Progress>,
     status: Option<Status>,
-
-    show_song_info: bool,
-    show_coverart: bool,
-    show_progress: bool,
-    show_options: bool,
+    mixramp: Option<MixRamp>,
+    replay_gain: Option<ReplayGainMode>,
+    layout: Layout,
 }
 
 
 impl Player {
-    pub fn new() -> Self {
+    pub fn new(layout: Layout) -> Self {
         Self {
             song_info: None,
             progress: None,
             status: None,
-
-            show_song_info: true,
-            show_coverart: true,
-            show_progress: true,
-            show_options: true,
+            mixramp: None,
+            replay_gain: None,
+            layout,
         }
     }
 
@@ -80,18 +112,72 @@ impl Player {
         self.status = Some(status);
     }
 
-    pub fn view(&self) -> Element<Cmd> {
+    pub fn update_modes(&mut self, mixramp: MixRamp, replay_gain: ReplayGainMode) {
+        self.mixramp = Some(mixramp);
+        self.replay_gain = Some(replay_gain);
+    }
+
+    pub fn adjust(&self, adjust: Adjust, delta: i32) -> Option<Cmd> {
+        match adjust {
+            Adjust::Crossfade => {
+                let secs = self.status.as_ref()?.crossfade.as_secs() as i64;
+                let secs = (secs + delta as i64).max(0) as u64;
+                Some(Cmd::SetCrossfade(Duration::from_secs(secs)))
+            }
+
+            Adjust::MixRampDb => {
+                let db = self.mixramp?.db.round() + delta as f32;
+                Some(Cmd::SetMixRampDb(db.min(0.0)))
+            }
+
+            Adjust::MixRampDelay => {
+                let secs = self.mixramp?.delay
+                    .map(|d| d.as_secs_f32().round() as i64)
+                    .unwrap_or(0);
+                let secs = secs + delta as i64;
+                Some(Cmd::SetMixRampDelay((secs > 0)
+                    .then(|| Duration::from_secs(secs as u64))))
+            }
+        }
+    }
+
+    pub fn next_single(&self) -> Option<Cmd> {
+        let single = self.status.as_ref()?.single;
+        Some(Cmd::SetSingle(next_choice(&SINGLE_MODES, single)))
+    }
+
+    pub fn next_replay_gain(&self) -> Option<Cmd> {
+        let mode = self.replay_gain?;
+        Some(Cmd::SetReplayGain(next_choice(&REPLAY_GAIN_MODES, mode)))
+    }
+
+    pub fn view<'a>(
+        &'a self,
+        permissions: &Permissions,
+        now_playing: &[Format],
+        coverart: Option<&image::Handle>,
+    ) -> Element<'a, Cmd> {
         use iced::{widget, Center, Fill};
 
+        // commands the password does not allow are greyed out
+        let allowed = |cmd: Cmd| permissions
+            .allows(cmd.mpd_command())
+            .then_some(cmd);
+
         let song_info = self.song_info
             .as_ref()
-            .map(|x| x.view(self.show_song_info, self.show_coverart))
+            .map(|x| x.view(
+                self.layout.show_song_info,
+                self.layout.show_cover_art,
+                now_playing,
+                coverart,
+            ))
             .unwrap_or(widget::text("").into());
 
         let progress_bar = self.progress
             .as_ref()
-            .filter(|_| self.show_progress)
-            .map(|x| x.view());
+            .filter(|_| self.layout.show_progress)
+            .map(|x| x.view(permissions.allows("seekcur")));
 
 
         let media_buttons = {
@@ -112,35 +198,43 @@ impl Player {
                 .push(widget::button(icon_prev)
                     .style(button_style)
                     .width(38)
-                    .on_press(Cmd::Prev)
+                    .on_press_maybe(allowed(Cmd::Prev))
                 )
                 .push(if self.is_playing() {
                     widget::button(icon_pause)
                         .style(button_style)
                         .width(50)
-                        .on_press(Cmd::Pause)
+                        .on_press_maybe(allowed(Cmd::Pause))
                 } else {
                     widget::button(icon_play)
                         .style(button_style)
                         .width(50)
-                        .on_press(Cmd::Play)
+                        .on_press_maybe(allowed(Cmd::Play))
                 })
                 .push(widget::button(icon_next)
                     .style(button_style)
                     .width(38)
-                    .on_press(Cmd::Next)
+                    .on_press_maybe(allowed(Cmd::Next))
                 )
         };
 
         let volume_slider = {
             let volume = self.get_volume();
-            let index = ((volume + 24) / 25) as usize;
+            let index = volume.div_ceil(25) as usize;
             let icon_volume = svg(ICONS_VOLUME[index].clone())
                 .width(20)
                 .style(icon_style_volume);
 
-            let slider = widget::slider(0..=100, self.get_volume(), Cmd::SetVolume)
-                .width(100);
+            let slider: Element<_> = if permissions.allows("setvol") {
+                widget::slider(0..=100, volume, Cmd::SetVolume)
+                    .width(100)
+                    .into()
+            } else {
+                widget::progress_bar(0.0..=100.0, volume as f32)
+                    .width(100)
+                    .height(4)
+                    .into()
+            };
 
             widget::Row::new()
                 .spacing(18)
@@ -155,7 +249,7 @@ impl Player {
             let timing = self.progress
                 .as_ref()
                 .map(|p| p.timing())
-                .unwrap_or(String::new());
+                .unwrap_or_default();
 
             let volume_container = Container::new(volume_slider)
                 .height(40)
@@ -176,32 +270,109 @@ impl Player {
 
         let option_togglers = self.status
             .as_ref()
-            .filter(|_| self.show_options)
+            .filter(|_| self.layout.show_options)
             .map(|status| widget::Row::new()
                 .push(widget::toggler(status.random)
                     .label("random")
                     .text_size(12)
-                    .on_toggle(Cmd::SetRandom)
+                    .on_toggle_maybe(permissions
+                        .allows("random")
+                        .then_some(Cmd::SetRandom))
                 )
                 .push(widget::toggler(status.repeat)
                     .label("loop")
                     .text_size(12)
-                    .on_toggle(Cmd::SetRepeat)
+                    .on_toggle_maybe(permissions
+                        .allows("repeat")
+                        .then_some(Cmd::SetRepeat))
                 )
                 .push(widget::toggler(status.consume)
                     .label("consume")
                     .text_size(12)
-                    .on_toggle(Cmd::SetConsume)
+                    .on_toggle_maybe(permissions
+                        .allows("consume")
+                        .then_some(Cmd::SetConsume))
                 )
                 .spacing(32)
                 .align_y(Center)
             );
 
+        let mode_settings = self.status
+            .as_ref()
+            .filter(|_| self.layout.show_options)
+            .map(|status| {
+                let label = |s: &'static str| widget::text(s).size(12);
+
+                let stepper = |name, value: String, adjust| widget::Row::new()
+                    .spacing(4)
+                    .align_y(Center)
+                    .push(label(name))
+                    .push(step_button("-", self.adjust(adjust, -1).and_then(allowed)))
+                    .push(widget::text(value).size(12))
+                    .push(step_button("+", self.adjust(adjust, 1).and_then(allowed)));
+
+                let mixramp_db = self.mixramp
+                    .map(|m| format!("{:.0} dB", m.db))
+                    .unwrap_or_default();
+
+                let mixramp_delay = match self.mixramp.map(|m| m.delay) {
+                    Some(Some(delay)) => format!("{:.0}s", delay.as_secs_f32()),
+                    Some(None) => String::from("off"),
+                    None => String::new(),
+                };
+
+                let single = SINGLE_MODES.iter()
+                    .find(|choice| choice.value == status.single)
+                    .copied();
+
+                let replay_gain = REPLAY_GAIN_MODES.iter()
+                    .find(|choice| Some(choice.value) == self.replay_gain)
+                    .copied();
+
+                // pick lists can't be disabled, show just the value instead
+                let single_list: Element<_> = if permissions.allows("single") {
+                    widget::pick_list(SINGLE_MODES, single, |choice| Cmd::SetSingle(choice.value))
+                        .text_size(12)
+                        .into()
+                } else {
+                    label(single.map(|c| c.label).unwrap_or_default()).into()
+                };
+
+                let replay_gain_list: Element<_> = if permissions.allows("replay_gain_mode") {
+                    widget::pick_list(REPLAY_GAIN_MODES, replay_gain,
+                        |choice| Cmd::SetReplayGain(choice.value))
+                        .text_size(12)
+                        .into()
+                } else {
+                    label(replay_gain.map(|c| c.label).unwrap_or_default()).into()
+                };
+
+                widget::Row::new()
+                    .push(widget::Row::new()
+                        .spacing(4)
+                        .align_y(Center)
+                        .push(label("single"))
+                        .push(single_list))
+                    .push(stepper("crossfade",
+                        format!("{}s", status.crossfade.as_secs()),
+                        Adjust::Crossfade))
+                    .push(stepper("mixramp", mixramp_db, Adjust::MixRampDb))
+                    .push(stepper("delay", mixramp_delay, Adjust::MixRampDelay))
+                    .push(widget::Row::new()
+                        .spacing(4)
+                        .align_y(Center)
+                        .push(label("replay gain"))
+                        .push(replay_gain_list))
+                    .spacing(24)
+                    .align_y(Center)
+            });
+
         widget::Column::new()
             .align_x(Center)
             .spacing(25)
             .padding(20)
             .push_maybe(option_togglers)
+            .push_maybe(mode_settings)
             .push(widget::center(song_info))
             .push(progress_and_control_bar)
             .into()
@@ -275,29 +446,51 @@ impl Player {
             .map(|status| status.consume)
     }
 
+    pub fn get_update_job(&self) -> Option<u64> {
+        self.status
+            .as_ref()
+            .and_then(|status| status.update_job)
+    }
+
+    pub fn get_partition(&self) -> Option<&str> {
+        self.status
+            .as_ref()
+            .and_then(|status| status.partition.as_deref())
+    }
+
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
     pub fn toggle_show_song_info(&mut self) {
-        self.show_song_info = !self.show_song_info;
+        self.layout.show_song_info = !self.layout.show_song_info;
     }
 
     pub fn toggle_show_coverart(&mut self) {
-        self.show_coverart = !self.show_coverart;
+        self.layout.show_cover_art = !self.layout.show_cover_art;
     }
 
     pub fn toggle_show_progress(&mut self) {
-        self.show_progress = !self.show_progress;
+        self.layout.show_progress = !self.layout.show_progress;
     }
 
     pub fn toggle_show_options(&mut self) {
-        self.show_options = !self.show_options;
+        self.layout.show_options = !self.layout.show_options;
     }
 
-    pub fn get_song_title(&self) -> Option<&str> {
-        self.song_info
-            .as_ref()
-            .map(|nfo| nfo.title.as_str())
+    pub fn get_song_info(&self) -> Option<&SongInfo> {
+        self.song_info.as_ref()
     }
 }
 
+fn step_button(label: &str, cmd: Option<Cmd>) -> Element<'_, Cmd> {
+    iced::widget::button(iced::widget::text(label).size(12))
+        .style(iced::widget::button::secondary)
+        .padding([0, 6])
+        .on_press_maybe(cmd)
+        .into()
+}
+
 fn icon_style_volume(theme: &Theme, _status: svg::Status) -> svg::Style {
     let pal = theme.extended_palette();
     let color = pal.secondary.base.color;
@@ -0,0 +1,306 @@
+use std::time::{Duration, Instant};
+use iced::{
+    widget::{self, scrollable::Viewport},
+    Element,
+    Task,
+};
+
+use crate::error::Error;
+use crate::mpd::{MpdCtrl, Cmd, AddMode, Permissions};
+use super::connected::ConMsg;
+use super::list::{ListScroll, selectable_row};
+use super::song_info::SongInfo;
+
+#[derive(Debug, Clone)]
+pub enum PlaylistsMsg {
+    Playlists(Vec<String>),
+    Select(String),
+    Songs(String, Vec<SongInfo>),
+    PressSong(usize),
+    Name(String),
+    Load(AddMode),
+    Save,
+    Rename,
+    Delete,
+    AddSongs(Vec<String>),
+    RemoveSong,
+    MoveUp,
+    MoveDown,
+    Scroll(usize, Viewport),
+}
+
+pub struct Playlists {
+    loaded: bool,
+    names: Vec<String>,
+    selected: Option<String>,
+    songs: Vec<SongInfo>,
+    song: Option<usize>,
+    last_click: Option<(usize, Instant)>,
+    name: String,
+    scroll: [ListScroll; 2],
+}
+
+impl Playlists {
+    const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+    pub fn new() -> Self {
+        Self {
+            loaded: false,
+            names: Vec::new(),
+            selected: None,
+            songs: Vec::new(),
+            song: None,
+            last_click: None,
+            name: String::new(),
+            scroll: Default::default(),
+        }
+    }
+
+    pub fn load(&mut self, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        if self.loaded {
+            return Task::none();
+        }
+        self.loaded = true;
+        self.refresh(ctrl)
+    }
+
+    pub fn refresh(&self, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        if !self.loaded {
+            return Task::none();
+        }
+
+        let cc = ctrl.clone();
+        let names = Task::perform(
+            async move { cc.get_playlists().await },
+            |result| result.map(|names| ConMsg::Playlists(PlaylistsMsg::Playlists(names))),
+        );
+
+        match &self.selected {
+            Some(name) => Task::batch([names, self.request_songs(ctrl, name.clone())]),
+            None => names,
+        }
+    }
+
+    pub fn update(&mut self, msg: PlaylistsMsg, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        let cmd = match msg {
+            PlaylistsMsg::Playlists(mut names) => {
+                names.sort_by_key(|name| name.to_lowercase());
+                self.names = names;
+
+                // the selected playlist may have been deleted or renamed
+                if self.selected.as_ref().is_some_and(|name| !self.names.contains(name)) {
+                    self.selected = None;
+                    self.songs.clear();
+                    self.song = None;
+                }
+                None
+            }
+
+            PlaylistsMsg::Select(name) => {
+                self.selected = Some(name.clone());
+                self.name = name.clone();
+                self.song = None;
+                return self.request_songs(ctrl, name);
+            }
+
+            PlaylistsMsg::Songs(name, songs) => {
+                if self.selected.as_ref() == Some(&name) {
+                    self.song = self.song.filter(|&i| i < songs.len());
+                    self.songs = songs;
+                }
+                None
+            }
+
+            PlaylistsMsg::PressSong(index) => {
+                let now = Instant::now();
+                let double = matches!(self.last_click,
+                    Some((last, time)) if last == index && now - time < Self::DOUBLE_CLICK);
+
+                self.song = Some(index);
+                if double {
+                    self.last_click = None;
+                    self.songs
+                        .get(index)
+                        .map(|info| Cmd::Add(vec![info.get_url().to_owned()], AddMode::Append))
+                } else {
+                    self.last_click = Some((index, now));
+                    None
+                }
+            }
+
+            PlaylistsMsg::Name(name) => {
+                self.name = name;
+                None
+            }
+
+            PlaylistsMsg::Load(mode) => self.selected
+                .clone()
+                .map(|name| Cmd::LoadPlaylist(name, mode)),
+
+            PlaylistsMsg::Save => self.new_name()
+                .map(Cmd::SavePlaylist),
+
+            PlaylistsMsg::Rename => {
+                let rename = self.selected
+                    .clone()
+                    .zip(self.new_name())
+                    .filter(|(from, to)| from != to);
+
+                // keep the playlist selected under its new name
+                rename.map(|(from, to)| {
+                    self.selected = Some(to.clone());
+                    Cmd::RenamePlaylist(from, to)
+                })
+            }
+
+            PlaylistsMsg::Delete => self.selected
+                .clone()
+                .map(Cmd::DeletePlaylist),
+
+            PlaylistsMsg::AddSongs(uris) => self.selected
+                .clone()
+                .filter(|_| !uris.is_empty())
+                .map(|name| Cmd::PlaylistAdd(name, uris)),
+
+            PlaylistsMsg::RemoveSong => self.selected
+                .clone()
+                .zip(self.song.take())
+                .map(|(name, pos)| Cmd::PlaylistDelete(name, vec![pos])),
+
+            PlaylistsMsg::MoveUp => self.move_song(-1),
+            PlaylistsMsg::MoveDown => self.move_song(1),
+
+            PlaylistsMsg::Scroll(column, viewport) => {
+                self.scroll[column].update(viewport);
+                None
+            }
+        };
+
+        match cmd {
+            Some(cmd) => Task::done(Ok(ConMsg::Cmd(cmd))),
+            None => Task::none(),
+        }
+    }
+
+    fn new_name(&self) -> Option<String> {
+        let name = self.name.trim();
+        (!name.is_empty()).then(|| name.to_owned())
+    }
+
+    fn move_song(&mut self, delta: isize) -> Option<Cmd> {
+        let name = self.selected.clone()?;
+        let from = self.song?;
+        let to = from.checked_add_signed(delta).filter(|&to| to < self.songs.len())?;
+
+        self.song = Some(to);
+        Some(Cmd::PlaylistMove(name, from, to))
+    }
+
+    fn request_songs(&self, ctrl: &MpdCtrl, name: String) -> Task<Result<ConMsg, Error>> {
+        let cc = ctrl.clone();
+        Task::perform(
+            async move {
+                let songs = cc.get_playlist(&name).await?;
+                Ok((name, songs))
+            },
+            |result| result.map(|(name, songs)| ConMsg::Playlists(PlaylistsMsg::Songs(
+                name,
+                songs.into_iter().map(SongInfo::from).collect(),
+            ))),
+        )
+    }
+
+    pub fn visible_songs(&self) -> Vec<&SongInfo> {
+        self.songs[self.scroll[1].visible(self.songs.len())]
+            .iter()
+            .collect()
+    }
+
+    pub fn view(&self, current: Option<&str>, permissions: &Permissions) -> Element<'_, PlaylistsMsg> {
+        use iced::{Center, Fill, FillPortion};
+
+        let allowed = |cmd: &str, msg: PlaylistsMsg, enabled: bool| permissions
+            .allows(cmd)
+            .then_some(msg)
+            .filter(|_| enabled);
+
+        let action = |label, msg: Option<PlaylistsMsg>| widget::button(widget::text(label).size(14))
+            .style(widget::button::secondary)
+            .on_press_maybe(msg);
+
+        let selected = self.selected.is_some();
+        let has_name = self.new_name().is_some();
+        let has_song = self.song.is_some();
+
+        let toolbar = widget::Row::new()
+            .spacing(5)
+            .align_y(Center)
+            .push(action("Load", allowed("load", PlaylistsMsg::Load(AddMode::Append), selected)))
+            .push(action("Insert", allowed("load", PlaylistsMsg::Load(AddMode::AfterCurrent), selected)))
+            .push(action("Replace", allowed("load", PlaylistsMsg::Load(AddMode::Replace), selected)))
+            .push(widget::horizontal_space().width(Fill))
+            .push(widget::text_input("Playlist name", &self.name)
+                .on_input(PlaylistsMsg::Name)
+                .size(14)
+                .width(200))
+            .push(action("Save queue", allowed("save", PlaylistsMsg::Save, has_name)))
+            .push(action("Rename", allowed("rename", PlaylistsMsg::Rename, selected && has_name)))
+            .push(action("Delete", allowed("rm", PlaylistsMsg::Delete, selected)));
+
+        let editbar = widget::Row::new()
+            .spacing(5)
+            .align_y(Center)
+            .push(action("Add current song", current
+                .and_then(|uri| allowed("playlistadd",
+                    PlaylistsMsg::AddSongs(vec![uri.to_owned()]),
+                    selected))))
+            .push(action("Remove", allowed("playlistdelete", PlaylistsMsg::RemoveSong, has_song)))
+            .push(action("Up", allowed("playlistmove", PlaylistsMsg::MoveUp, has_song)))
+            .push(action("Down", allowed("playlistmove", PlaylistsMsg::MoveDown, has_song)))
+            .push(widget::horizontal_space().width(Fill))
+            .push(widget::text(format!("{} songs", self.songs.len())).size(14));
+
+        let names = self.scroll[0].view(
+            self.names.len(),
+            |i| {
+                let name = &self.names[i];
+                selectable_row(
+                    name.as_str(),
+                    self.selected.as_ref() == Some(name),
+                    PlaylistsMsg::Select(name.clone()),
+                )
+            },
+            |viewport| PlaylistsMsg::Scroll(0, viewport),
+        );
+
+        let songs = self.scroll[1].view(
+            self.songs.len(),
+            |i| {
+                let info = &self.songs[i];
+                let label = if info.artist.is_empty() {
+                    format!("{:>3}. {}", i + 1, info.title)
+                } else {
+                    format!("{:>3}. {} - {}", i + 1, info.artist, info.title)
+                };
+                selectable_row(label, self.song == Some(i), PlaylistsMsg::PressSong(i))
+            },
+            |viewport| PlaylistsMsg::Scroll(1, viewport),
+        );
+
+        let columns = widget::Row::new()
+            .spacing(10)
+            .push(widget::container(names).width(FillPortion(1)))
+            .push(widget::Column::new()
+                .spacing(5)
+                .width(FillPortion(2))
+                .push(editbar)
+                .push(songs));
+
+        widget::Column::new()
+            .spacing(10)
+            .padding(10)
+            .push(toolbar)
+            .push(columns)
+            .into()
+    }
+}
@@ -24,12 +24,18 @@ impl Progress {
         }
     }
 
-    pub fn view(&self) -> iced::Element<'_, Cmd> {
-        use iced::widget::slider;
+    pub fn view(&self, seekable: bool) -> iced::Element<'_, Cmd> {
+        use iced::widget::{slider, progress_bar};
 
         let duration = self.duration.as_secs_f32();
         let elapsed = self.elapsed();
 
+        if !seekable {
+            return progress_bar(0.0..=duration, elapsed)
+                .height(Self::HEIGHT)
+                .into();
+        }
+
         // Create a slider and style it to look more like a progress bar
         slider(0.0..=duration, elapsed, |s| Cmd::Seek(Duration::from_secs_f32(s)))
             .height(Self::HEIGHT)
@@ -1,31 +1,307 @@
-use std::collections::HashMap;
-use bytes::BytesMut;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use iced::{
+    keyboard::Modifiers,
+    widget::{self, scrollable::Viewport},
+    Element,
+    Theme,
+};
 use mpd_client::{
     responses::SongInQueue,
-    commands::SongId,
+    commands::{SongId, SongPosition},
 };
 
+use crate::format::Format;
+use crate::mpd::{Cmd, Permissions};
 use super::song_info::SongInfo;
+use super::list::ListScroll;
+
+#[derive(Debug, Clone)]
+pub enum QueueMsg {
+    Press(SongId),
+    Release(SongId),
+    Hover(SongId),
+    Scroll(Viewport),
+    PlaySelected,
+    RemoveSelected,
+    MoveUp,
+    MoveDown,
+    Crop,
+    Clear,
+}
 
 #[derive(Default)]
 pub struct Queue {
+    order: Vec<SongId>,
     infos: HashMap<SongId, SongInfo>,
+
+    selected: HashSet<SongId>,
+    anchor: Option<SongId>,
+    last_click: Option<(SongId, Instant)>,
+    drag: Option<SongId>,
+    drag_over: Option<SongId>,
+    modifiers: Modifiers,
+    scroll: ListScroll,
 }
 
 impl Queue {
+    const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
     pub fn update(&mut self, queue: Vec<SongInQueue>) {
+        self.order = queue.iter().map(|v| v.id).collect();
         self.infos = queue.into_iter()
             .map(|v| (v.id, v.into()))
             .collect();
-    }
 
-    pub fn update_coverart(&mut self, id: SongId, data: Option<BytesMut>) {
-        if let Some(info) = self.infos.get_mut(&id) {
-            info.update_coverart(data);
-        }
+        // forget about songs which are gone
+        self.selected.retain(|id| self.infos.contains_key(id));
+        self.drag = None;
+        self.drag_over = None;
     }
 
     pub fn get(&self, id: &SongId) -> Option<&SongInfo> {
         self.infos.get(id)
     }
+
+    pub fn upcoming(&self, current: Option<SongId>, count: usize) -> impl Iterator<Item = &SongInfo> {
+        let start = current
+            .and_then(|id| self.position(&id))
+            .map_or(0, |pos| pos + 1);
+
+        self.order
+            .iter()
+            .skip(start)
+            .take(count)
+            .filter_map(|id| self.infos.get(id))
+    }
+
+    pub fn visible_songs(&self) -> Vec<&SongInfo> {
+        self.order[self.scroll.visible(self.order.len())]
+            .iter()
+            .filter_map(|id| self.infos.get(id))
+            .collect()
+    }
+
+    pub fn set_modifiers(&mut self, modifiers: Modifiers) {
+        self.modifiers = modifiers;
+    }
+
+    fn position(&self, id: &SongId) -> Option<usize> {
+        self.order.iter().position(|x| x == id)
+    }
+
+    fn selection(&self) -> Vec<(usize, SongId)> {
+        self.order.iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, id)| self.selected.contains(id))
+            .collect()
+    }
+
+    pub fn handle(&mut self, msg: QueueMsg) -> Option<Cmd> {
+        match msg {
+            QueueMsg::Press(id) => {
+                let now = Instant::now();
+                let double = matches!(self.last_click,
+                    Some((last, time)) if last == id && now - time < Self::DOUBLE_CLICK);
+
+                if double {
+                    self.last_click = None;
+                    self.drag = None;
+                    return Some(Cmd::PlayId(id));
+                }
+                self.last_click = Some((id, now));
+
+                if self.modifiers.control() {
+                    if !self.selected.remove(&id) {
+                        self.selected.insert(id);
+                    }
+                    self.anchor = Some(id);
+                } else if let (true, Some(anchor)) = (self.modifiers.shift(), self.anchor) {
+                    let a = self.position(&anchor).unwrap_or(0);
+                    let b = self.position(&id).unwrap_or(0);
+                    self.selected = self.order[a.min(b)..=a.max(b)]
+                        .iter()
+                        .copied()
+                        .collect();
+                } else {
+                    self.selected = HashSet::from([id]);
+                    self.anchor = Some(id);
+                }
+
+                self.drag = Some(id);
+                None
+            }
+
+            QueueMsg::Hover(id) => {
+                if self.drag.is_some() {
+                    self.drag_over = Some(id);
+                }
+                None
+            }
+
+            QueueMsg::Release(id) => {
+                self.drag_over = None;
+                let from = self.drag.take().filter(|from| *from != id)?;
+                let to = self.position(&id)?;
+                Some(Cmd::Move(vec![(from, SongPosition(to))]))
+            }
+
+            QueueMsg::Scroll(viewport) => {
+                self.scroll.update(viewport);
+                None
+            }
+
+            QueueMsg::PlaySelected => {
+                self.selection()
+                    .first()
+                    .map(|(_, id)| Cmd::PlayId(*id))
+            }
+
+            QueueMsg::RemoveSelected => {
+                let ids: Vec<_> = self.selection()
+                    .into_iter()
+                    .map(|(_, id)| id)
+                    .collect();
+
+                (!ids.is_empty()).then_some(Cmd::Delete(ids))
+            }
+
+            QueueMsg::MoveUp => {
+                let selection = self.selection();
+                if selection.first().is_none_or(|(pos, _)| *pos == 0) {
+                    return None;
+                }
+
+                // moving in ascending order keeps the positions of the
+                // songs still to be moved intact
+                Some(Cmd::Move(selection
+                    .into_iter()
+                    .map(|(pos, id)| (id, SongPosition(pos - 1)))
+                    .collect()))
+            }
+
+            QueueMsg::MoveDown => {
+                let selection = self.selection();
+                if selection.last().is_none_or(|(pos, _)| pos + 1 >= self.order.len()) {
+                    return None;
+                }
+
+                Some(Cmd::Move(selection
+                    .into_iter()
+                    .rev()
+                    .map(|(pos, id)| (id, SongPosition(pos + 1)))
+                    .collect()))
+            }
+
+            QueueMsg::Crop => Some(Cmd::Crop),
+            QueueMsg::Clear => Some(Cmd::ClearQueue),
+        }
+    }
+
+    pub fn view<'a>(
+        &'a self,
+        current: Option<SongId>,
+        permissions: &Permissions,
+        columns: &[Format],
+    ) -> Element<'a, QueueMsg> {
+        use iced::{Center, Fill};
+
+        let allowed = |cmd: &str, msg: QueueMsg| permissions
+            .allows(cmd)
+            .then_some(msg);
+
+        let has_selection = !self.selected.is_empty();
+        let action = |label, cmd: &str, msg: QueueMsg, needs_selection: bool| {
+            widget::button(widget::text(label).size(14))
+                .style(widget::button::secondary)
+                .on_press_maybe(allowed(cmd, msg)
+                    .filter(|_| has_selection || !needs_selection))
+        };
+
+        let toolbar = widget::Row::new()
+            .spacing(5)
+            .align_y(Center)
+            .push(action("Play", "playid", QueueMsg::PlaySelected, true))
+            .push(action("Remove", "deleteid", QueueMsg::RemoveSelected, true))
+            .push(action("Up", "moveid", QueueMsg::MoveUp, true))
+            .push(action("Down", "moveid", QueueMsg::MoveDown, true))
+            .push(action("Crop", "deleteid", QueueMsg::Crop, false))
+            .push(action("Clear", "clear", QueueMsg::Clear, false))
+            .push(widget::horizontal_space().width(Fill))
+            .push(widget::text(format!("{} songs", self.order.len())).size(14));
+
+        let row = |index: usize| {
+            let id = self.order[index];
+            let info = &self.infos[&id];
+
+            let kind = if self.drag_over == Some(id) && self.drag != Some(id) {
+                RowKind::DropTarget
+            } else if self.selected.contains(&id) {
+                RowKind::Selected
+            } else if current == Some(id) {
+                RowKind::Current
+            } else {
+                RowKind::Normal
+            };
+
+            let cell = |s: String| widget::text(s)
+                .size(14)
+                .wrapping(widget::text::Wrapping::None);
+
+            let line = widget::Row::new()
+                .spacing(10)
+                .padding([0, 5])
+                .align_y(Center)
+                .push(cell(format!("{}", index + 1)).width(40))
+                // the first column is the widest
+                .extend(columns.iter().enumerate().map(|(i, format)| {
+                    let portion = if i == 0 { 3 } else { 2 };
+                    cell(format.render(info))
+                        .width(iced::FillPortion(portion))
+                        .into()
+                }))
+                .push(cell(info.duration_str()).width(50));
+
+            let content = widget::container(line)
+                .height(Fill)
+                .align_y(Center)
+                .style(move |theme| row_style(theme, kind));
+
+            widget::mouse_area(content)
+                .on_press(QueueMsg::Press(id))
+                .on_release(QueueMsg::Release(id))
+                .on_enter(QueueMsg::Hover(id))
+                .into()
+        };
+
+        widget::Column::new()
+            .spacing(10)
+            .padding(10)
+            .push(toolbar)
+            .push(self.scroll.view(self.order.len(), row, QueueMsg::Scroll))
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RowKind {
+    Normal,
+    Current,
+    Selected,
+    DropTarget,
+}
+
+fn row_style(theme: &Theme, kind: RowKind) -> widget::container::Style {
+    let pal = theme.extended_palette();
+    let pair = match kind {
+        RowKind::Normal => return widget::container::Style::default(),
+        RowKind::Current => pal.primary.weak,
+        RowKind::Selected => pal.secondary.base,
+        RowKind::DropTarget => pal.primary.strong,
+    };
+
+    widget::container::Style::default()
+        .background(pair.color)
+        .color(pair.text)
 }
@@ -0,0 +1,127 @@
+use std::time::{Duration, Instant};
+use iced::{widget, Element, Theme};
+
+use crate::error::Error;
+use super::connected::Connected;
+
+// state while the connection to MPD is lost and we try to get it back.
+//
+// The last known state of the player is kept, so it can be shown
+// (greyed out) until the connection is reestablished.
+pub struct Reconnect {
+    error: Error,
+    attempt: u32,
+    retry_at: Option<Instant>,
+    last: Option<Box<Connected>>,
+}
+
+impl Reconnect {
+    const INITIAL_DELAY: Duration = Duration::from_secs(1);
+    const MAX_DELAY: Duration = Duration::from_secs(60);
+
+    pub fn new(error: Error, last: Option<Box<Connected>>) -> Self {
+        Self {
+            error,
+            attempt: 0,
+            retry_at: Some(Instant::now() + Self::INITIAL_DELAY),
+            last,
+        }
+    }
+
+    pub fn failed(&mut self, error: Error) {
+        self.attempt = self.attempt.saturating_add(1);
+        self.retry_at = Some(Instant::now() + self.delay());
+        self.error = error;
+    }
+
+    pub fn is_due(&mut self) -> bool {
+        match self.retry_at {
+            Some(time) if time <= Instant::now() => {
+                self.retry_at = None;
+                true
+            }
+
+            _ => false,
+        }
+    }
+
+    pub fn is_waiting(&self) -> bool {
+        self.retry_at.is_some()
+    }
+
+    pub fn retry_now(&mut self) {
+        self.retry_at = None;
+    }
+
+    pub fn last(&self) -> Option<&Connected> {
+        self.last.as_deref()
+    }
+
+    pub fn take_last(&mut self) -> Option<Box<Connected>> {
+        self.last.take()
+    }
+
+    fn delay(&self) -> Duration {
+        let factor = 1u32.checked_shl(self.attempt).unwrap_or(u32::MAX);
+        Self::INITIAL_DELAY
+            .saturating_mul(factor)
+            .min(Self::MAX_DELAY)
+    }
+
+    pub fn view<'a, M>(&'a self, retry: M, last: Option<Element<'a, M>>) -> Element<'a, M>
+    where
+        M: Clone + 'a,
+    {
+        use iced::Center;
+
+        let countdown = match self.retry_at {
+            Some(time) => {
+                let secs = time
+                    .saturating_duration_since(Instant::now())
+                    .as_secs_f32()
+                    .ceil();
+                format!("Reconnecting in {secs}s (attempt {})", self.attempt + 1)
+            }
+
+            None => String::from("Reconnecting..."),
+        };
+
+        let info = widget::Column::new()
+            .spacing(20)
+            .align_x(Center)
+            .push(widget::text("Connection lost").size(40))
+            .push(widget::text(self.error.to_string()).size(20))
+            .push(widget::text(countdown).size(16))
+            .push(widget::button("Reconnect now")
+                .on_press_maybe(self.retry_at.is_some().then_some(retry)));
+
+        overlay(info, last)
+    }
+}
+
+pub fn overlay<'a, M: 'a>(
+    info: impl Into<Element<'a, M>>,
+    last: Option<Element<'a, M>>,
+) -> Element<'a, M> {
+    use iced::Fill;
+
+    let overlay = widget::container(info)
+        .center(Fill)
+        .style(overlay_style);
+
+    match last {
+        Some(last) => widget::stack![last, widget::opaque(overlay)].into(),
+        None => overlay.into(),
+    }
+}
+
+fn overlay_style(theme: &Theme) -> widget::container::Style {
+    let pal = theme.extended_palette();
+    let color = iced::Color {
+        a: 0.85,
+        ..pal.background.base.color
+    };
+
+    widget::container::Style::default()
+        .background(color)
+}
@@ -0,0 +1,295 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+use iced::{
+    task,
+    widget::{self, scrollable::Viewport},
+    Element,
+    Task,
+};
+use mpd_client::{
+    filter::{Filter, Operator},
+    tag::Tag,
+};
+
+use crate::error::Error;
+use crate::mpd::{MpdCtrl, Cmd, AddMode, Permissions};
+use super::connected::ConMsg;
+use super::list::{ListScroll, selected_style};
+use super::song_info::SongInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchField {
+    Any,
+    File,
+    Title,
+    Artist,
+    Album,
+    AlbumArtist,
+    Genre,
+    Composer,
+    Performer,
+    Date,
+    Comment,
+}
+
+impl SearchField {
+    const ALL: [SearchField; 11] = [
+        SearchField::Any,
+        SearchField::File,
+        SearchField::Title,
+        SearchField::Artist,
+        SearchField::Album,
+        SearchField::AlbumArtist,
+        SearchField::Genre,
+        SearchField::Composer,
+        SearchField::Performer,
+        SearchField::Date,
+        SearchField::Comment,
+    ];
+
+    fn tag(&self) -> Tag {
+        match self {
+            SearchField::Any => Tag::any(),
+            SearchField::File => Tag::Other("file".into()),
+            SearchField::Title => Tag::Title,
+            SearchField::Artist => Tag::Artist,
+            SearchField::Album => Tag::Album,
+            SearchField::AlbumArtist => Tag::AlbumArtist,
+            SearchField::Genre => Tag::Genre,
+            SearchField::Composer => Tag::Composer,
+            SearchField::Performer => Tag::Performer,
+            SearchField::Date => Tag::Date,
+            SearchField::Comment => Tag::Comment,
+        }
+    }
+}
+
+impl fmt::Display for SearchField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            SearchField::Any => "Everything",
+            SearchField::File => "File path",
+            SearchField::Title => "Title",
+            SearchField::Artist => "Artist",
+            SearchField::Album => "Album",
+            SearchField::AlbumArtist => "Album artist",
+            SearchField::Genre => "Genre",
+            SearchField::Composer => "Composer",
+            SearchField::Performer => "Performer",
+            SearchField::Date => "Date",
+            SearchField::Comment => "Comment",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SearchMsg {
+    Query(String),
+    Field(SearchField),
+    Results(u64, Vec<SongInfo>),
+    Press(usize),
+    Add(AddMode),
+    Scroll(Viewport),
+}
+
+pub struct Search {
+    query: String,
+    field: SearchField,
+    // counts the searches started, to drop results of outdated ones.
+    generation: u64,
+    // the search currently waiting or running, aborted when dropped.
+    pending: Option<task::Handle>,
+    results: Vec<SongInfo>,
+    selected: Option<usize>,
+    last_click: Option<(usize, Instant)>,
+    scroll: ListScroll,
+}
+
+impl Search {
+    const DEBOUNCE: Duration = Duration::from_millis(300);
+    const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+    const LIMIT: usize = 1000;
+
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            field: SearchField::Any,
+            generation: 0,
+            pending: None,
+            results: Vec::new(),
+            selected: None,
+            last_click: None,
+            scroll: ListScroll::default(),
+        }
+    }
+
+    pub fn update(&mut self, msg: SearchMsg, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        match msg {
+            SearchMsg::Query(query) => {
+                self.query = query;
+                self.start(ctrl)
+            }
+
+            SearchMsg::Field(field) => {
+                self.field = field;
+                self.start(ctrl)
+            }
+
+            SearchMsg::Results(generation, results) => {
+                if generation == self.generation {
+                    self.pending = None;
+                    self.results = results;
+                    self.selected = None;
+                    self.last_click = None;
+                }
+                Task::none()
+            }
+
+            SearchMsg::Press(index) => {
+                let now = Instant::now();
+                let double = matches!(self.last_click,
+                    Some((last, time)) if last == index && now - time < Self::DOUBLE_CLICK);
+
+                self.selected = Some(index);
+                if double {
+                    self.last_click = None;
+                    self.update(SearchMsg::Add(AddMode::Append), ctrl)
+                } else {
+                    self.last_click = Some((index, now));
+                    Task::none()
+                }
+            }
+
+            SearchMsg::Add(mode) => {
+                // the selected song, otherwise all results
+                let uris: Vec<_> = match self.selected.and_then(|i| self.results.get(i)) {
+                    Some(info) => vec![info.get_url().to_owned()],
+                    None => self.results.iter().map(|info| info.get_url().to_owned()).collect(),
+                };
+
+                if uris.is_empty() {
+                    Task::none()
+                } else {
+                    Task::done(Ok(ConMsg::Cmd(Cmd::Add(uris, mode))))
+                }
+            }
+
+            SearchMsg::Scroll(viewport) => {
+                self.scroll.update(viewport);
+                Task::none()
+            }
+        }
+    }
+
+    pub fn refresh(&mut self, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        if self.results.is_empty() && self.pending.is_none() {
+            return Task::none();
+        }
+        self.start(ctrl)
+    }
+
+    fn start(&mut self, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        self.generation += 1;
+        self.pending = None;
+
+        // every word has to match
+        let filter = self.query
+            .split_whitespace()
+            .map(|word| Filter::new(self.field.tag(), Operator::Contain, word))
+            .reduce(Filter::and);
+
+        let Some(filter) = filter else {
+            self.results.clear();
+            self.selected = None;
+            return Task::none();
+        };
+
+        let generation = self.generation;
+        let cc = ctrl.clone();
+        let (task, handle) = Task::perform(
+            async move {
+                tokio::time::sleep(Self::DEBOUNCE).await;
+                cc.search(filter, Self::LIMIT).await
+            },
+            move |result| result
+                .map(|songs| ConMsg::Search(SearchMsg::Results(
+                    generation,
+                    songs.into_iter().map(SongInfo::from).collect(),
+                ))),
+        ).abortable();
+
+        self.pending = Some(handle.abort_on_drop());
+        task
+    }
+
+    pub fn visible_songs(&self) -> Vec<&SongInfo> {
+        self.results[self.scroll.visible(self.results.len())]
+            .iter()
+            .collect()
+    }
+
+    pub fn view(&self, permissions: &Permissions) -> Element<'_, SearchMsg> {
+        use iced::{Center, Fill, FillPortion};
+
+        let can_add = permissions.allows("add") && !self.results.is_empty();
+        let action = |label, mode| widget::button(widget::text(label).size(14))
+            .style(widget::button::secondary)
+            .on_press_maybe(can_add.then_some(SearchMsg::Add(mode)));
+
+        let status = if self.pending.is_some() {
+            String::from("searching…")
+        } else if self.results.len() >= Self::LIMIT {
+            format!("first {} songs", Self::LIMIT)
+        } else {
+            format!("{} songs", self.results.len())
+        };
+
+        let toolbar = widget::Row::new()
+            .spacing(5)
+            .align_y(Center)
+            .push(widget::text_input("Search", &self.query)
+                .on_input(SearchMsg::Query)
+                .size(14)
+                .width(FillPortion(3)))
+            .push(widget::pick_list(SearchField::ALL, Some(self.field), SearchMsg::Field)
+                .text_size(14))
+            .push(widget::horizontal_space().width(FillPortion(1)))
+            .push(widget::text(status).size(14))
+            .push(action("Add", AddMode::Append))
+            .push(action("Insert", AddMode::AfterCurrent))
+            .push(action("Replace", AddMode::Replace));
+
+        let row = |index: usize| {
+            let info = &self.results[index];
+            let selected = self.selected == Some(index);
+
+            let cell = |s: String| widget::text(s)
+                .size(14)
+                .wrapping(widget::text::Wrapping::None);
+
+            let line = widget::Row::new()
+                .spacing(10)
+                .padding([0, 5])
+                .align_y(Center)
+                .push(cell(info.title.clone()).width(FillPortion(3)))
+                .push(cell(info.artist.clone()).width(FillPortion(2)))
+                .push(cell(info.album.clone()).width(FillPortion(2)))
+                .push(cell(info.duration_str()).width(50));
+
+            widget::mouse_area(widget::container(line)
+                .height(Fill)
+                .align_y(Center)
+                .style(move |theme| selected_style(theme, selected)))
+                .on_press(SearchMsg::Press(index))
+                .into()
+        };
+
+        widget::Column::new()
+            .spacing(10)
+            .padding(10)
+            .push(toolbar)
+            .push(self.scroll.view(self.results.len(), row, SearchMsg::Scroll))
+            .into()
+    }
+}
@@ -1,46 +1,61 @@
-use bytes::BytesMut;
-use mpd_client::responses::SongInQueue;
+use std::time::Duration;
+use mpd_client::{
+    responses::{Song, SongInQueue},
+    tag::Tag,
+};
 use iced::{
     widget::image,
     Element,
 };
 
+use crate::cover::album_key;
+use crate::format::{Field, Fields, Format};
 use crate::mpd::Cmd;
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct SongInfo {
     pub title: String,
     pub artist: String,
     pub album: String,
+    pub duration: Option<Duration>,
+    pub tags: Vec<(Tag, Vec<String>)>,
+    pub format: Option<String>,
     url: String,
-    coverart: Option<image::Handle>,
-    missing_cover: bool,
 }
 
 impl SongInfo {
-    pub fn view(&self, show_info: bool, show_art: bool) -> Element<Cmd> {
+    pub fn view<'a>(
+        &'a self,
+        show_info: bool,
+        show_art: bool,
+        lines: &[Format],
+        coverart: Option<&image::Handle>,
+    ) -> Element<'a, Cmd> {
         use iced::{font, widget, Font, Center, Fill};
 
-        let coverart = self.coverart
-            .as_ref()
+        let coverart = coverart
             .filter(|_| show_art)
             .map(|handle| image(handle.clone()).height(Fill));
 
         let description = if show_info {
-            let title = widget::text(&self.title)
-                .size(26)
-                .font(Font { weight: font::Weight::Bold, ..Font::default() });
-            let artist = widget::text(&self.artist)
-                .size(16);
-            let album = widget::text(&self.album)
-                .size(16);
-
-            Some(widget::Column::new()
+            let lines = lines.iter()
+                .map(|format| format.render(self))
+                .filter(|line| !line.is_empty())
+                .enumerate()
+                .map(|(i, line)| if i == 0 {
+                    widget::text(line)
+                        .size(26)
+                        .font(Font { weight: font::Weight::Bold, ..Font::default() })
+                        .into()
+                } else {
+                    widget::text(line)
+                        .size(16)
+                        .into()
+                });
+
+            Some(widget::Column::with_children(lines)
                 .spacing(5)
                 .align_x(Center)
-                .push(title)
-                .push(artist)
-                .push(album)
             )
         } else {
             None
@@ -54,39 +69,148 @@ impl SongInfo {
             .into()
     }
 
-    pub fn is_cover_missing(&self) -> bool {
-        self.missing_cover
+    pub fn album_key(&self) -> Option<String> {
+        let tag = |tag| self.tags
+            .iter()
+            .find(|(x, _)| *x == tag)
+            .and_then(|(_, values)| values.first())
+            .map(String::as_str);
+
+        album_key(&self.url, tag(Tag::AlbumArtist), tag(Tag::Album))
     }
 
-    pub fn update_coverart(&mut self, data: Option<BytesMut>) {
-        self.coverart = data.map(image::Handle::from_bytes);
-        self.missing_cover = false;
+    pub fn cover_key(&self) -> String {
+        self.album_key()
+            .unwrap_or_else(|| format!("file:{}", self.url))
     }
 
     pub fn get_url(&self) -> &str {
         self.url.as_str()
     }
+
+    pub fn duration_str(&self) -> String {
+        self.duration
+            .map(|d| {
+                let secs = d.as_secs();
+                format!("{}:{:02}", secs / 60, secs % 60)
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Fields for SongInfo {
+    fn field(&self, field: &Field) -> Option<String> {
+        match field {
+            Field::Tag(tag) => self.tags
+                .iter()
+                .find(|(x, _)| x == tag)
+                .map(|(_, values)| values.join(", ")),
+            Field::File => Some(self.url.clone()),
+            Field::FileName => self.url
+                .rsplit('/')
+                .next()
+                .map(str::to_owned),
+            Field::Duration => Some(self.duration_str()),
+        }
+    }
 }
 
 impl From<SongInQueue> for SongInfo {
     fn from(nfo: SongInQueue) -> Self {
-        let title = if let Some(title) = nfo.song.title() {
+        nfo.song.into()
+    }
+}
+
+impl From<Song> for SongInfo {
+    fn from(song: Song) -> Self {
+        let title = if let Some(title) = song.title() {
             title.to_owned()
         } else {
             use std::path::Path;
-            let path = Path::new(&nfo.song.url);
+            let path = Path::new(&song.url);
             path.file_stem()
                 .map(|s| s.to_string_lossy().to_string())
                 .unwrap_or(String::from("<unknown file>"))
         };
 
+        let mut tags: Vec<_> = song.tags
+            .iter()
+            .map(|(tag, values)| (tag_order(tag), tag.clone(), values.clone()))
+            .collect();
+        tags.sort();
+
         Self {
             title,
-            artist: nfo.song.artists().join(", "),
-            album: nfo.song.album().unwrap_or("").to_owned(),
-            url: nfo.song.url.clone(),
-            coverart: None,
-            missing_cover: true,
+            artist: song.artists().join(", "),
+            album: song.album().unwrap_or("").to_owned(),
+            duration: song.duration,
+            tags: tags.into_iter()
+                .map(|(_, tag, values)| (tag, values))
+                .collect(),
+            format: song.format.clone(),
+            url: song.url,
         }
     }
 }
+
+const TAG_ORDER: [Tag; 14] = [
+    Tag::Title,
+    Tag::Artist,
+    Tag::Album,
+    Tag::AlbumArtist,
+    Tag::Composer,
+    Tag::Conductor,
+    Tag::Performer,
+    Tag::Work,
+    Tag::Date,
+    Tag::OriginalDate,
+    Tag::Track,
+    Tag::Disc,
+    Tag::Genre,
+    Tag::Label,
+];
+
+fn tag_order(tag: &Tag) -> usize {
+    TAG_ORDER.iter()
+        .position(|x| x == tag)
+        .unwrap_or(TAG_ORDER.len())
+}
+
+pub fn tag_name(tag: &Tag) -> String {
+    let name = match tag {
+        Tag::Album => "Album",
+        Tag::AlbumArtist => "Album artist",
+        Tag::AlbumArtistSort => "Album artist (sort)",
+        Tag::AlbumSort => "Album (sort)",
+        Tag::Artist => "Artist",
+        Tag::ArtistSort => "Artist (sort)",
+        Tag::Comment => "Comment",
+        Tag::Composer => "Composer",
+        Tag::ComposerSort => "Composer (sort)",
+        Tag::Conductor => "Conductor",
+        Tag::Date => "Date",
+        Tag::Disc => "Disc",
+        Tag::Ensemble => "Ensemble",
+        Tag::Genre => "Genre",
+        Tag::Grouping => "Grouping",
+        Tag::Label => "Label",
+        Tag::Location => "Location",
+        Tag::Movement => "Movement",
+        Tag::MovementNumber => "Movement number",
+        Tag::MusicBrainzArtistId => "MusicBrainz artist id",
+        Tag::MusicBrainzRecordingId => "MusicBrainz recording id",
+        Tag::MusicBrainzReleaseArtistId => "MusicBrainz album artist id",
+        Tag::MusicBrainzReleaseId => "MusicBrainz album id",
+        Tag::MusicBrainzTrackId => "MusicBrainz track id",
+        Tag::MusicBrainzWorkId => "MusicBrainz work id",
+        Tag::Name => "Name",
+        Tag::OriginalDate => "Original date",
+        Tag::Performer => "Performer",
+        Tag::Title => "Title",
+        Tag::Track => "Track",
+        Tag::Work => "Work",
+        Tag::Other(raw) => return raw.to_string(),
+        _ => return format!("{tag:?}"),
+    };
+    name.to_owned()
+}
@@ -0,0 +1,362 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+use iced::{
+    widget,
+    Element,
+    Task,
+};
+use mpd_client::{
+    responses::{Count, Stats},
+    tag::Tag,
+};
+
+use crate::error::Error;
+use crate::mpd::MpdCtrl;
+use super::connected::ConMsg;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Measure {
+    Songs,
+    PlayTime,
+}
+
+impl Measure {
+    const ALL: [Measure; 2] = [Measure::Songs, Measure::PlayTime];
+
+    fn of(&self, count: &Total) -> f32 {
+        match self {
+            Measure::Songs => count.songs as f32,
+            Measure::PlayTime => count.playtime.as_secs_f32(),
+        }
+    }
+}
+
+impl fmt::Display for Measure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Measure::Songs => write!(f, "by songs"),
+            Measure::PlayTime => write!(f, "by play time"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Total {
+    songs: u64,
+    playtime: Duration,
+}
+
+impl From<Count> for Total {
+    fn from(count: Count) -> Self {
+        Self { songs: count.songs, playtime: count.playtime }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Statistics {
+    stats: Stats,
+    genres: Vec<(String, Total)>,
+    decades: Vec<(String, Total)>,
+    album_artists: Vec<(String, Total)>,
+}
+
+#[derive(Debug, Clone)]
+pub enum StatsMsg {
+    Loaded(Box<Statistics>),
+    Refresh,
+    Measure(Measure),
+}
+
+pub struct StatsView {
+    loaded: bool,
+    statistics: Option<Statistics>,
+    measure: Measure,
+    // the largest groups by measure, per chart
+    charts: Vec<(&'static str, Vec<(String, Total)>)>,
+}
+
+impl StatsView {
+    const BARS: usize = 15;
+
+    pub fn new() -> Self {
+        Self {
+            loaded: false,
+            statistics: None,
+            measure: Measure::Songs,
+            charts: Vec::new(),
+        }
+    }
+
+    pub fn load(&mut self, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        if self.loaded {
+            return Task::none();
+        }
+        self.loaded = true;
+        self.refresh(ctrl)
+    }
+
+    pub fn refresh(&self, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        if !self.loaded {
+            return Task::none();
+        }
+
+        let cc = ctrl.clone();
+        Task::perform(
+            async move {
+                Ok(Statistics {
+                    stats: cc.get_stats().await?,
+                    genres: totals(cc.count_grouped(Tag::Genre).await?),
+                    decades: by_decade(totals(cc.count_grouped(Tag::Date).await?)),
+                    album_artists: totals(cc.count_grouped(Tag::AlbumArtist).await?),
+                })
+            },
+            |result| result.map(|stats| ConMsg::Stats(StatsMsg::Loaded(Box::new(stats)))),
+        )
+    }
+
+    pub fn update(&mut self, msg: StatsMsg, ctrl: &MpdCtrl) -> Task<Result<ConMsg, Error>> {
+        match msg {
+            StatsMsg::Loaded(statistics) => {
+                self.statistics = Some(*statistics);
+                self.sort_charts();
+                Task::none()
+            }
+
+            StatsMsg::Refresh => self.refresh(ctrl),
+
+            StatsMsg::Measure(measure) => {
+                self.measure = measure;
+                self.sort_charts();
+                Task::none()
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, StatsMsg> {
+        use iced::{Center, Fill, FillPortion};
+
+        let toolbar = widget::Row::new()
+            .spacing(5)
+            .align_y(Center)
+            .push(widget::pick_list(Measure::ALL, Some(self.measure), StatsMsg::Measure)
+                .text_size(14))
+            .push(widget::horizontal_space().width(Fill))
+            .push(widget::button(widget::text("Refresh").size(14))
+                .style(widget::button::secondary)
+                .on_press(StatsMsg::Refresh));
+
+        let Some(statistics) = &self.statistics else {
+            return widget::Column::new()
+                .padding(10)
+                .push(toolbar)
+                .into();
+        };
+
+        let stats = &statistics.stats;
+        let numbers = [
+            ("Artists", stats.artists.to_string()),
+            ("Albums", stats.albums.to_string()),
+            ("Songs", stats.songs.to_string()),
+            ("Database play time", format_duration(stats.db_playtime)),
+            ("Last database update", format_timestamp(stats.db_last_update)),
+            ("Uptime", format_duration(stats.uptime)),
+            ("Time played", format_duration(stats.playtime)),
+        ];
+
+        let numbers = widget::Column::with_children(numbers.into_iter().map(|(label, value)|
+            widget::Row::new()
+                .spacing(10)
+                .push(widget::text(label).size(14).width(200))
+                .push(widget::text(value).size(14))
+                .into()))
+            .spacing(4);
+
+        let charts = widget::Row::with_children(self.charts.iter().map(|(title, groups)|
+            self.view_chart(title, groups).width(FillPortion(1)).into()))
+            .spacing(20);
+
+        let content = widget::Column::new()
+            .spacing(20)
+            .push(numbers)
+            .push(charts);
+
+        widget::Column::new()
+            .spacing(10)
+            .padding(10)
+            .push(toolbar)
+            .push(widget::scrollable(content).height(Fill))
+            .into()
+    }
+
+    fn sort_charts(&mut self) {
+        let Some(statistics) = &self.statistics else {
+            self.charts.clear();
+            return;
+        };
+
+        self.charts = [
+            ("Genres", &statistics.genres),
+            ("Decades", &statistics.decades),
+            ("Album artists", &statistics.album_artists),
+        ].into_iter()
+            .map(|(title, groups)| (title, largest(groups, self.measure, Self::BARS)))
+            .collect();
+    }
+
+    fn view_chart<'a>(&self, title: &'a str, groups: &'a [(String, Total)]) -> widget::Column<'a, StatsMsg> {
+        let max = groups.iter()
+            .map(|(_, count)| self.measure.of(count))
+            .fold(1.0, f32::max);
+
+        let bars = groups.iter().map(|(name, count)| {
+            let name = if name.is_empty() { "(none)" } else { name };
+            let value = format!("{} songs, {}", count.songs, format_duration(count.playtime));
+
+            widget::Column::new()
+                .push(widget::Row::new()
+                    .push(widget::text(name)
+                        .size(12)
+                        .width(iced::Fill)
+                        .wrapping(widget::text::Wrapping::None))
+                    .push(widget::text(value).size(12)))
+                .push(widget::progress_bar(0.0..=max, self.measure.of(count)).height(6))
+                .into()
+        });
+
+        widget::Column::new()
+            .spacing(6)
+            .push(widget::text(title).size(16))
+            .extend(bars)
+    }
+}
+
+fn totals(counts: Vec<(String, Count)>) -> Vec<(String, Total)> {
+    counts.into_iter()
+        .map(|(name, count)| (name, count.into()))
+        .collect()
+}
+
+fn largest(groups: &[(String, Total)], measure: Measure, bars: usize) -> Vec<(String, Total)> {
+    let mut groups = groups.to_vec();
+    groups.sort_by(|a, b| measure.of(&b.1).total_cmp(&measure.of(&a.1)));
+
+    if groups.len() > bars {
+        let rest = groups.split_off(bars - 1);
+        groups.push((format!("{} others", rest.len()), sum(rest.iter().map(|(_, c)| c))));
+    }
+    groups
+}
+
+fn by_decade(dates: Vec<(String, Total)>) -> Vec<(String, Total)> {
+    let mut decades: BTreeMap<String, Vec<Total>> = BTreeMap::new();
+
+    for (date, count) in dates {
+        // dates start with the year, but may be anything
+        let decade = date.get(..4)
+            .and_then(|year| year.parse::<u32>().ok())
+            .map(|year| format!("{}s", year / 10 * 10))
+            .unwrap_or_default();
+        decades.entry(decade).or_default().push(count);
+    }
+
+    decades.into_iter()
+        .map(|(decade, counts)| (decade, sum(counts.iter())))
+        .collect()
+}
+
+fn sum<'a>(counts: impl Iterator<Item = &'a Total>) -> Total {
+    counts.fold(Total::default(), |mut total, count| {
+        total.songs += count.songs;
+        total.playtime += count.playtime;
+        total
+    })
+}
+
+fn format_duration(d: Duration) -> String {
+    let mins = d.as_secs() / 60;
+    let (days, hours, mins) = (mins / (24 * 60), mins / 60 % 24, mins % 60);
+
+    if days > 0 {
+        format!("{days}d {hours}h {mins}m")
+    } else if hours > 0 {
+        format!("{hours}h {mins}m")
+    } else {
+        format!("{mins}m")
+    }
+}
+
+fn format_timestamp(secs: u64) -> String {
+    if secs == 0 {
+        return String::from("never");
+    }
+
+    // civil date from days since 1970-01-01, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = (secs / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    let time = secs % 86400;
+    format!("{year}-{month:02}-{day:02} {:02}:{:02} UTC", time / 3600, time / 60 % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn total(songs: u64, secs: u64) -> Total {
+        Total { songs, playtime: Duration::from_secs(secs) }
+    }
+
+    fn songs(groups: &[(String, Total)]) -> Vec<(&str, u64)> {
+        groups.iter().map(|(name, count)| (name.as_str(), count.songs)).collect()
+    }
+
+    #[test]
+    fn decades() {
+        let dates = vec![
+            (String::from("1994"), total(2, 100)),
+            (String::from("1999-05-01"), total(3, 200)),
+            (String::from("2001"), total(1, 50)),
+            (String::from("unknown"), total(4, 10)),
+            (String::new(), total(5, 20)),
+        ];
+        let decades = by_decade(dates);
+
+        assert_eq!(songs(&decades), [("", 9), ("1990s", 5), ("2000s", 1)]);
+        assert_eq!(decades[1].1.playtime, Duration::from_secs(300));
+    }
+
+    #[test]
+    fn largest_groups() {
+        let groups: Vec<_> = (1..=5)
+            .map(|n| (format!("g{n}"), total(n, 10 - n)))
+            .collect();
+
+        assert_eq!(songs(&largest(&groups, Measure::Songs, 3)), [("g5", 5), ("g4", 4), ("3 others", 6)]);
+        assert_eq!(songs(&largest(&groups, Measure::PlayTime, 5)), [("g1", 1), ("g2", 2), ("g3", 3), ("g4", 4), ("g5", 5)]);
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(format_timestamp(0), "never");
+        assert_eq!(format_timestamp(1), "1970-01-01 00:00 UTC");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00 UTC");
+        assert_eq!(format_timestamp(1_700_000_000), "2023-11-14 22:13 UTC");
+        assert_eq!(format_timestamp(4_107_542_399), "2100-02-28 23:59 UTC");
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(Duration::from_secs(59)), "0m");
+        assert_eq!(format_duration(Duration::from_secs(3 * 3600 + 120)), "3h 2m");
+        assert_eq!(format_duration(Duration::from_secs(86400 + 60)), "1d 0h 1m");
+    }
+}
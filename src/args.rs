@@ -0,0 +1,126 @@
+use std::path::PathBuf;
+
+// command line arguments, parsed by hand to keep the dependency list short.
+#[derive(Debug, Default)]
+pub struct Args {
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub config: Option<PathBuf>,
+    pub profile: Option<String>,
+    pub help: bool,
+}
+
+impl Args {
+    pub const USAGE: &str = "\
+usage: mpdcli [options]
+
+options:
+  -h, --host HOST   connect to HOST ([password@]host, /path/to/socket or @abstract)
+  -p, --port PORT   connect to PORT (tcp only)
+  -c, --config FILE read configuration from FILE
+      --profile NAME use the connection profile NAME from the configuration
+      --help        print this help and exit
+
+Without options the selected profile is used, then the MPD_HOST and MPD_PORT
+environment variables and then the settings of
+$XDG_CONFIG_HOME/mpdcli/config.toml.";
+
+    pub fn parse() -> Result<Self, String> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    fn parse_from(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut result = Self::default();
+
+        while let Some(arg) = args.next() {
+            // allow the --flag=value form as well
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--")
+                    => (flag.to_owned(), Some(value.to_owned())),
+
+                _ => (arg, None),
+            };
+
+            let mut value = || inline
+                .clone()
+                .or_else(|| args.next())
+                .ok_or(format!("missing value for {flag}"));
+
+            match flag.as_str() {
+                "--help" => result.help = true,
+
+                "-h" | "--host" => result.host = Some(value()?),
+
+                "-c" | "--config" => result.config = Some(value()?.into()),
+
+                "--profile" => result.profile = Some(value()?),
+
+                "-p" | "--port" => {
+                    let port = value()?;
+                    result.port = Some(port
+                        .parse()
+                        .map_err(|_| format!("invalid port: {port}"))?);
+                }
+
+                _ => return Err(format!("unknown option: {flag}")),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse_from(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn no_options() {
+        let args = parse(&[]).unwrap();
+        assert_eq!(args.host, None);
+        assert_eq!(args.port, None);
+        assert_eq!(args.config, None);
+        assert_eq!(args.profile, None);
+        assert!(!args.help);
+    }
+
+    #[test]
+    fn options() {
+        let args = parse(&["-h", "secret@musicbox", "-p", "6601", "-c", "mpdcli.toml"]).unwrap();
+        assert_eq!(args.host.as_deref(), Some("secret@musicbox"));
+        assert_eq!(args.port, Some(6601));
+        assert_eq!(args.config, Some(PathBuf::from("mpdcli.toml")));
+
+        let args = parse(&["--host", "/run/mpd/socket", "--profile", "office", "--help"]).unwrap();
+        assert_eq!(args.host.as_deref(), Some("/run/mpd/socket"));
+        assert_eq!(args.profile.as_deref(), Some("office"));
+        assert!(args.help);
+    }
+
+    #[test]
+    fn inline_values() {
+        let args = parse(&["--host=@mpd", "--port=6601", "--profile=living room"]).unwrap();
+        assert_eq!(args.host.as_deref(), Some("@mpd"));
+        assert_eq!(args.port, Some(6601));
+        assert_eq!(args.profile.as_deref(), Some("living room"));
+
+        // a password may contain '='
+        let args = parse(&["--host=a=b@musicbox"]).unwrap();
+        assert_eq!(args.host.as_deref(), Some("a=b@musicbox"));
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(parse(&["--host"]).is_err());
+        assert!(parse(&["-p", "http"]).is_err());
+        assert!(parse(&["--port=70000"]).is_err());
+        assert!(parse(&["--verbose"]).is_err());
+        assert!(parse(&["musicbox"]).is_err());
+        // the short forms take no inline value
+        assert!(parse(&["-h=musicbox"]).is_err());
+    }
+}
@@ -0,0 +1,275 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use serde::Deserialize;
+
+use crate::format::Format;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub theme: String,
+    pub redraw_interval: u64,
+    pub connection: ConnectionConfig,
+    pub layout: Layout,
+    pub seek: SeekSteps,
+    pub cover: CoverConfig,
+    pub format: FormatConfig,
+    pub default_profile: Option<String>,
+    pub profiles: BTreeMap<String, ConnectionConfig>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConnectionConfig {
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub password: Option<String>,
+    pub partition: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Layout {
+    pub show_options: bool,
+    pub show_song_info: bool,
+    pub show_cover_art: bool,
+    pub show_progress: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SeekSteps {
+    pub short: u64,
+    pub medium: u64,
+    pub long: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum SeekStep {
+    Short,
+    Medium,
+    Long,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CoverConfig {
+    pub binary_limit: usize,
+    pub cache: bool,
+    pub cache_size: u64,
+    pub cache_max_age: u64,
+    // memory for covers in MiB, counted as the decoded images.
+    pub memory_budget: usize,
+    pub sources: Vec<CoverSource>,
+    pub local_names: Vec<String>,
+    pub pick_largest: bool,
+    pub prefetch: usize,
+    pub prefetch_concurrency: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoverSource {
+    Local,
+    AlbumArt,
+    ReadPicture,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FormatConfig {
+    pub window_title: Format,
+    pub now_playing: Vec<Format>,
+    pub queue: Vec<Format>,
+    pub notification: Option<Format>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: String::from("Kanagawa Dragon"),
+            redraw_interval: 250,
+            connection: ConnectionConfig::default(),
+            layout: Layout::default(),
+            seek: SeekSteps::default(),
+            cover: CoverConfig::default(),
+            format: FormatConfig::default(),
+            default_profile: None,
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            show_options: true,
+            show_song_info: true,
+            show_cover_art: true,
+            show_progress: true,
+        }
+    }
+}
+
+impl Default for SeekSteps {
+    fn default() -> Self {
+        Self {
+            short: 10,
+            medium: 30,
+            long: 300,
+        }
+    }
+}
+
+impl Default for CoverConfig {
+    fn default() -> Self {
+        Self {
+            binary_limit: 655360,
+            cache: true,
+            cache_size: 100,
+            cache_max_age: 30,
+            memory_budget: 256,
+            sources: vec![CoverSource::Local, CoverSource::AlbumArt, CoverSource::ReadPicture],
+            local_names: [
+                "cover.jpg", "cover.png", "folder.jpg", "folder.png",
+                "front.jpg", "front.png", "album.jpg", "album.png",
+                "Scans/", "Artwork/",
+            ].map(String::from).to_vec(),
+            pick_largest: false,
+            prefetch: 10,
+            prefetch_concurrency: 2,
+        }
+    }
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        let parse = |source| Format::parse(source).expect("invalid default format");
+        Self {
+            window_title: parse("%title%|%filename%"),
+            now_playing: vec![
+                parse("%title%|%filename%"),
+                parse("%artist%"),
+                parse("%album%"),
+            ],
+            queue: vec![
+                parse("%title%|%filename%"),
+                parse("%artist%"),
+                parse("%album%"),
+            ],
+            notification: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("mpdcli").join("config.toml"))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content)
+                .map_err(|error| format!("{}: {error}", path.display())),
+
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                tracing::debug!("no config file at {}", path.display());
+                Ok(Self::default())
+            }
+
+            Err(error) => Err(format!("{}: {error}", path.display())),
+        }
+    }
+
+    pub fn connection(&self, profile: Option<&str>) -> Result<&ConnectionConfig, String> {
+        match profile {
+            Some(name) => self.profiles
+                .get(name)
+                .ok_or(format!("unknown profile: {name}")),
+
+            None => Ok(&self.connection),
+        }
+    }
+
+    pub fn profile_names(&self) -> Vec<String> {
+        self.profiles.keys().cloned().collect()
+    }
+
+    pub fn theme(&self) -> iced::Theme {
+        let name = normalize(&self.theme);
+        iced::Theme::ALL
+            .iter()
+            .find(|theme| normalize(&theme.to_string()) == name)
+            .cloned()
+            .unwrap_or_else(|| {
+                tracing::warn!("unknown theme: {}", self.theme);
+                iced::Theme::KanagawaDragon
+            })
+    }
+
+    pub fn redraw_interval(&self) -> Duration {
+        Duration::from_millis(self.redraw_interval.max(10))
+    }
+}
+
+impl SeekSteps {
+    pub fn get(&self, step: SeekStep) -> Duration {
+        let secs = match step {
+            SeekStep::Short => self.short,
+            SeekStep::Medium => self.medium,
+            SeekStep::Long => self.long,
+        };
+        Duration::from_secs(secs)
+    }
+}
+
+impl Layout {
+    // store the layout in the config file at `path`.
+    //
+    // The file is edited in place, so comments and formatting of the
+    // other settings are preserved.
+    pub async fn save(self, path: PathBuf) -> Result<(), String> {
+        use toml_edit::{DocumentMut, Item, Table, value};
+
+        let content = match tokio::fs::read_to_string(&path).await {
+            Ok(content) => content,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(error.to_string()),
+        };
+
+        let mut doc: DocumentMut = content
+            .parse()
+            .map_err(|error: toml_edit::TomlError| error.to_string())?;
+
+        let layout = doc
+            .entry("layout")
+            .or_insert(Item::Table(Table::new()))
+            .as_table_mut()
+            .ok_or("layout is not a table")?;
+
+        layout["show_options"] = value(self.show_options);
+        layout["show_song_info"] = value(self.show_song_info);
+        layout["show_cover_art"] = value(self.show_cover_art);
+        layout["show_progress"] = value(self.show_progress);
+
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|error| error.to_string())?;
+        }
+
+        tokio::fs::write(&path, doc.to_string())
+            .await
+            .map_err(|error| error.to_string())
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
@@ -0,0 +1,71 @@
+mod disk_cache;
+mod local;
+mod store;
+
+use bytes::BytesMut;
+
+use crate::config::{CoverConfig, CoverSource};
+use crate::error::{AckCode, Error};
+use crate::mpd::MpdCtrl;
+
+use local::LocalCovers;
+
+pub use disk_cache::DiskCache;
+pub use store::CoverStore;
+
+#[derive(Debug, Clone)]
+pub struct CoverLookup {
+    sources: Vec<CoverSource>,
+    local: LocalCovers,
+}
+
+impl CoverLookup {
+    pub fn new(config: &CoverConfig) -> Self {
+        Self {
+            sources: config.sources.clone(),
+            local: LocalCovers::new(config),
+        }
+    }
+
+    pub async fn fetch(&self, ctrl: &MpdCtrl, uri: &str) -> Result<Option<BytesMut>, Error> {
+        for source in &self.sources {
+            let result = match source {
+                CoverSource::Local => match ctrl.music_directory() {
+                    Some(dir) => Ok(self.local.find(dir, uri).await),
+                    None => Ok(None),
+                },
+                CoverSource::AlbumArt => ctrl.get_album_art(uri).await,
+                CoverSource::ReadPicture => ctrl.get_embedded_picture(uri).await,
+            };
+
+            match result {
+                Ok(Some(data)) => return Ok(Some(data)),
+                Ok(None) => tracing::debug!("no cover from {source:?} for {uri}"),
+
+                // no such file, or a server without this command
+                Err(error) if matches!(error.ack_code(), Some(AckCode::NoExist | AckCode::Unknown))
+                    => tracing::debug!("no cover from {source:?} for {uri}: {error}"),
+
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+// key identifying the album of a song, so its songs share one cover.
+//
+// MPD looks for cover art in the directory of a song, so this is the
+// directory. Songs without one (streams or files in the root of the
+// music directory) fall back to album artist and album.
+pub fn album_key(uri: &str, album_artist: Option<&str>, album: Option<&str>) -> Option<String> {
+    if !uri.contains("://") {
+        if let Some((dir, _)) = uri.rsplit_once('/') {
+            return Some(format!("dir:{dir}"));
+        }
+    }
+
+    let album = album.filter(|album| !album.is_empty())?;
+    Some(format!("tag:{}\n{album}", album_artist.unwrap_or_default()))
+}
@@ -0,0 +1,212 @@
+use std::{
+    fs::FileTimes,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
+use bytes::BytesMut;
+
+use crate::config::CoverConfig;
+
+// cover art stored in `$XDG_CACHE_HOME/mpdcli/covers`, so it survives
+// restarts.
+//
+// Each album is one file named by a hash of the server and the cover
+// key. Albums without cover art get an empty `.none` file, so MPD is not
+// asked again each time. Entries older than the maximum age are fetched
+// again. When the cache grows beyond its size limit, the least recently
+// used ones are removed: the age is taken from the modification time,
+// the last use from the access time, which is set on each load.
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    dir: Option<PathBuf>,
+    server: String,
+    size_limit: u64,
+    max_age: Duration,
+    // shared by all clones, so the downloads running at the same time
+    // prune only once in a while and one at a time.
+    stores: Arc<AtomicUsize>,
+    pruning: Arc<AtomicBool>,
+}
+
+impl DiskCache {
+    // missing covers are looked for again sooner, they may have been
+    // added since.
+    const MISSING_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+    // number of covers stored between prunes, the first one prunes
+    // right after starting.
+    const PRUNE_INTERVAL: usize = 32;
+
+    pub fn new(config: &CoverConfig, server: &str) -> Self {
+        let dir = dirs::cache_dir()
+            .filter(|_| config.cache)
+            .map(|dir| dir.join("mpdcli").join("covers"));
+
+        Self {
+            dir,
+            server: server.to_owned(),
+            size_limit: config.cache_size.saturating_mul(1024 * 1024),
+            max_age: Duration::from_secs(config.cache_max_age.saturating_mul(24 * 60 * 60)),
+            stores: Arc::new(AtomicUsize::new(0)),
+            pruning: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub async fn load(&self, key: &str) -> Option<Option<BytesMut>> {
+        let path = self.path(key)?;
+
+        if is_fresh(&path, self.max_age).await {
+            match tokio::fs::read(&path).await {
+                Ok(data) => {
+                    touch(&path).await;
+                    return Some(Some(BytesMut::from(data.as_slice())));
+                }
+                Err(error) => tracing::warn!("failed to read {}: {error}", path.display()),
+            }
+        }
+
+        if is_fresh(&path.with_extension("none"), Self::MISSING_MAX_AGE).await {
+            return Some(None);
+        }
+
+        None
+    }
+
+    pub async fn store(&self, key: &str, data: Option<&BytesMut>) {
+        let Some(path) = self.path(key) else {
+            return;
+        };
+
+        if let Err(error) = self.write(&path, data).await {
+            tracing::warn!("failed to store cover in {}: {error}", path.display());
+            return;
+        }
+
+        if !self.stores.fetch_add(1, Ordering::Relaxed).is_multiple_of(Self::PRUNE_INTERVAL) {
+            return;
+        }
+
+        if self.pruning.swap(true, Ordering::Acquire) {
+            return;
+        }
+        if let Err(error) = self.prune().await {
+            tracing::warn!("failed to prune the cover cache: {error}");
+        }
+        self.pruning.store(false, Ordering::Release);
+    }
+
+    fn path(&self, key: &str) -> Option<PathBuf> {
+        let dir = self.dir.as_ref()?;
+        let hash = fnv1a(format!("{}\n{key}", self.server).as_bytes());
+        Some(dir.join(format!("{hash:016x}")))
+    }
+
+    async fn write(&self, path: &Path, data: Option<&BytesMut>) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+
+        let missing = path.with_extension("none");
+        match data {
+            Some(data) => {
+                // the download may be cancelled while writing, so never
+                // leave a partial file behind the final name
+                let partial = path.with_extension("part");
+                tokio::fs::write(&partial, data).await?;
+                tokio::fs::rename(&partial, path).await?;
+                remove(&missing).await
+            }
+
+            None => {
+                tokio::fs::write(&missing, []).await?;
+                remove(path).await
+            }
+        }
+    }
+
+    async fn prune(&self) -> std::io::Result<()> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+
+        let mut entries = Vec::new();
+        let mut total = 0;
+        let mut dir = match tokio::fs::read_dir(dir).await {
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            result => result?,
+        };
+        while let Some(entry) = dir.next_entry().await? {
+            let path = entry.path();
+            // written right now, renamed when complete
+            if path.extension().is_some_and(|ext| ext == "part") {
+                continue;
+            }
+
+            // removed meanwhile, by a cover written or found missing
+            let meta = match entry.metadata().await {
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => continue,
+                result => result?,
+            };
+            let used = meta.accessed().or_else(|_| meta.modified())?;
+            total += meta.len();
+            entries.push((used, meta.len(), path));
+        }
+
+        if total <= self.size_limit {
+            return Ok(());
+        }
+
+        entries.sort();
+        for (_, len, path) in entries {
+            if total <= self.size_limit {
+                break;
+            }
+            tracing::debug!("removing cached cover {}", path.display());
+            remove(&path).await?;
+            total -= len;
+        }
+
+        Ok(())
+    }
+}
+
+async fn is_fresh(path: &Path, max_age: Duration) -> bool {
+    let Ok(meta) = tokio::fs::metadata(path).await else {
+        return false;
+    };
+
+    meta.modified()
+        .ok()
+        .and_then(|time| time.elapsed().ok())
+        .is_some_and(|age| age <= max_age)
+}
+
+async fn touch(path: &Path) {
+    let result = match tokio::fs::File::open(path).await {
+        Ok(file) => file
+            .into_std()
+            .await
+            .set_times(FileTimes::new().set_accessed(SystemTime::now())),
+        Err(error) => Err(error),
+    };
+
+    if let Err(error) = result {
+        tracing::debug!("failed to touch {}: {error}", path.display());
+    }
+}
+
+async fn remove(path: &Path) -> std::io::Result<()> {
+    match tokio::fs::remove_file(path).await {
+        Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
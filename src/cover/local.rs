@@ -0,0 +1,110 @@
+use std::path::{Path, PathBuf};
+use bytes::BytesMut;
+
+use crate::config::CoverConfig;
+
+#[derive(Debug, Clone)]
+pub struct LocalCovers {
+    // file names looked for (ignoring case), names ending in `/` are
+    // directories of which all images count.
+    names: Vec<String>,
+    largest: bool,
+}
+
+impl LocalCovers {
+    const EXTENSIONS: [&str; 6] = ["jpg", "jpeg", "png", "webp", "gif", "bmp"];
+
+    pub fn new(config: &CoverConfig) -> Self {
+        Self {
+            names: config.local_names.clone(),
+            largest: config.pick_largest,
+        }
+    }
+
+    pub async fn find(&self, music_dir: &Path, uri: &str) -> Option<BytesMut> {
+        if uri.contains("://") {
+            return None;
+        }
+        let dir = music_dir.join(uri).parent()?.to_owned();
+
+        let mut best: Option<(usize, Vec<u8>)> = None;
+        for path in self.candidates(&dir).await {
+            let data = match tokio::fs::read(&path).await {
+                Ok(data) => data,
+                Err(error) => {
+                    tracing::debug!("failed to read {}: {error}", path.display());
+                    continue;
+                }
+            };
+
+            if !self.largest {
+                tracing::debug!("using cover {}", path.display());
+                return Some(BytesMut::from(data.as_slice()));
+            }
+
+            let Ok(size) = imagesize::blob_size(&data) else {
+                tracing::debug!("not an image: {}", path.display());
+                continue;
+            };
+            let pixels = size.width * size.height;
+            if best.as_ref().is_none_or(|(most, _)| pixels > *most) {
+                tracing::debug!("cover candidate {} with {}x{}",
+                    path.display(), size.width, size.height);
+                best = Some((pixels, data));
+            }
+        }
+
+        best.map(|(_, data)| BytesMut::from(data.as_slice()))
+    }
+
+    async fn candidates(&self, dir: &Path) -> Vec<PathBuf> {
+        let entries = list_dir(dir).await;
+
+        let mut found = Vec::new();
+        for name in &self.names {
+            let (name, is_dir) = match name.strip_suffix('/') {
+                Some(name) => (name, true),
+                None => (name.as_str(), false),
+            };
+
+            let Some(path) = entries.iter().find(|path| path
+                .file_name()
+                .is_some_and(|file| file.eq_ignore_ascii_case(name)))
+            else {
+                continue;
+            };
+
+            if !is_dir {
+                found.push(path.clone());
+                continue;
+            }
+
+            let mut images: Vec<_> = list_dir(path)
+                .await
+                .into_iter()
+                .filter(|path| path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| Self::EXTENSIONS
+                        .iter()
+                        .any(|x| x.eq_ignore_ascii_case(ext))))
+                .collect();
+            images.sort();
+            found.extend(images);
+        }
+
+        found
+    }
+}
+
+async fn list_dir(dir: &Path) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return paths;
+    };
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        paths.push(entry.path());
+    }
+    paths
+}
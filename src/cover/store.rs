@@ -0,0 +1,182 @@
+use std::collections::{HashMap, HashSet};
+use bytes::BytesMut;
+use iced::widget::image;
+
+// cover art in memory, one image per album.
+//
+// Songs of an album share the same handle, so the image is also only
+// decoded once. When the images take more than the memory budget, the
+// least recently used ones are dropped (and fetched again from the
+// disk cache when needed). Pinned covers, like those on screen, are
+// kept even beyond the budget.
+pub struct CoverStore {
+    budget: usize,
+    used: usize,
+    clock: u64,
+    entries: HashMap<String, Entry>,
+    pinned: HashSet<String>,
+}
+
+struct Entry {
+    cover: Option<image::Handle>,
+    size: usize,
+    last_used: u64,
+}
+
+impl CoverStore {
+    // bookkeeping per entry, so albums without cover count as well.
+    const ENTRY_SIZE: usize = 128;
+
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            used: 0,
+            clock: 0,
+            entries: HashMap::new(),
+            pinned: HashSet::new(),
+        }
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&image::Handle> {
+        self.entries.get(key)?.cover.as_ref()
+    }
+
+    pub fn touch(&mut self, key: &str) {
+        self.clock += 1;
+        if let Some(entry) = self.entries.get_mut(key) {
+            entry.last_used = self.clock;
+        }
+    }
+
+    pub fn pin(&mut self, keys: HashSet<String>) {
+        self.pinned = keys;
+        self.evict();
+    }
+
+    pub fn insert(&mut self, key: String, data: Option<BytesMut>) {
+        let size = Self::ENTRY_SIZE + data.as_deref().map_or(0, memory_size);
+        let entry = Entry {
+            cover: data.map(image::Handle::from_bytes),
+            size,
+            last_used: 0,
+        };
+
+        self.used += size;
+        if let Some(old) = self.entries.insert(key.clone(), entry) {
+            self.used -= old.size;
+        }
+        self.touch(&key);
+        self.evict();
+    }
+
+    // drop the least recently used covers until the budget is kept,
+    // except for the pinned ones and the most recent one.
+    fn evict(&mut self) {
+        while self.used > self.budget {
+            let oldest = self.entries
+                .iter()
+                .filter(|(key, entry)| entry.last_used != self.clock && !self.pinned.contains(*key))
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+
+            let Some(key) = oldest else {
+                break;
+            };
+            if let Some(entry) = self.entries.remove(&key) {
+                self.used -= entry.size;
+            }
+        }
+    }
+}
+
+fn memory_size(data: &[u8]) -> usize {
+    let pixels = imagesize::blob_size(data)
+        .map_or(0, |size| size.width.saturating_mul(size.height));
+    data.len().saturating_add(pixels.saturating_mul(4))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cover(size: usize) -> Option<BytesMut> {
+        Some(BytesMut::zeroed(size - CoverStore::ENTRY_SIZE))
+    }
+
+    fn keys(keys: &[&str]) -> HashSet<String> {
+        keys.iter().map(|key| key.to_string()).collect()
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut store = CoverStore::new(3000);
+        store.insert(String::from("a"), cover(1000));
+        store.insert(String::from("b"), cover(1000));
+        store.insert(String::from("c"), cover(1000));
+        store.touch("a");
+        store.insert(String::from("d"), cover(1000));
+
+        assert!(store.contains("a"));
+        assert!(!store.contains("b"));
+        assert!(store.contains("c"));
+        assert!(store.contains("d"));
+    }
+
+    #[test]
+    fn keeps_the_newest() {
+        let mut store = CoverStore::new(1000);
+        store.insert(String::from("a"), cover(500));
+        store.insert(String::from("b"), cover(5000));
+
+        assert!(!store.contains("a"));
+        assert!(store.contains("b"));
+        assert!(store.get("b").is_some());
+    }
+
+    #[test]
+    fn keeps_pinned() {
+        let mut store = CoverStore::new(2000);
+        store.pin(keys(&["a", "b"]));
+        store.insert(String::from("a"), cover(1000));
+        store.insert(String::from("b"), cover(1000));
+        store.insert(String::from("c"), cover(1000));
+        store.insert(String::from("d"), cover(1000));
+
+        // over budget rather than dropping a pinned cover
+        assert!(store.contains("a"));
+        assert!(store.contains("b"));
+        assert!(!store.contains("c"));
+        assert!(store.contains("d"));
+
+        // unpinned ones go as soon as the budget needs it
+        store.pin(keys(&["d"]));
+        assert!(!store.contains("a"));
+        assert!(store.contains("b"));
+        assert!(store.contains("d"));
+    }
+
+    #[test]
+    fn counts_decoded_pixels() {
+        // the header of a 100x50 PNG is enough to know its size
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend(100u32.to_be_bytes());
+        png.extend(50u32.to_be_bytes());
+        png.extend([8, 6, 0, 0, 0, 0, 0, 0, 0]);
+
+        assert_eq!(memory_size(&png), png.len() + 100 * 50 * 4);
+        assert_eq!(memory_size(b"not an image"), 12);
+    }
+
+    #[test]
+    fn missing_covers_are_known() {
+        let mut store = CoverStore::new(1000);
+        store.insert(String::from("a"), None);
+
+        assert!(store.contains("a"));
+        assert!(store.get("a").is_none());
+    }
+}
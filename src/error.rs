@@ -4,62 +4,190 @@ use mpd_client::{
     protocol::MpdProtocolError,
     client::CommandError,
     client::ConnectionError,
+    client::ConnectWithPasswordError,
 };
 
 #[derive(Clone, Debug)]
 pub enum Error {
-    Io(io::ErrorKind),
-    Mpd(String),
-    MpdErrorResponse(u64),
+    Io(io::ErrorKind, String),
+    Ack(Ack),
+    Protocol(String),
+    InvalidResponse(String),
     InvalidQueue,
+    IncorrectPassword,
     SendError(mpsc::SendError),
     Disconnect,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    Retryable,
+    Permission,
+    Fatal,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ack {
+    pub code: AckCode,
+    pub message: String,
+    pub command: Option<String>,
+    pub index: u64,
+}
+
+// error codes of MPD, see `src/protocol/Ack.hxx` in its sources.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AckCode {
+    NotList,
+    Arg,
+    Password,
+    Permission,
+    Unknown,
+    NoExist,
+    PlaylistMax,
+    System,
+    PlaylistLoad,
+    UpdateAlready,
+    PlayerSync,
+    Exist,
+    Other(u64),
+}
+
+impl Error {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            // a bad address or an unsupported socket type stays so
+            Self::Io(io::ErrorKind::InvalidInput | io::ErrorKind::Unsupported, _)
+                => ErrorKind::Fatal,
+            Self::Io(..) | Self::Protocol(_) | Self::Disconnect => ErrorKind::Retryable,
+            Self::IncorrectPassword => ErrorKind::Permission,
+            Self::Ack(ack) => match ack.code {
+                AckCode::Password | AckCode::Permission => ErrorKind::Permission,
+                _ => ErrorKind::Fatal,
+            },
+            Self::InvalidResponse(_) | Self::InvalidQueue | Self::SendError(_) => ErrorKind::Fatal,
+        }
+    }
+
+    pub fn ack_code(&self) -> Option<AckCode> {
+        match self {
+            Self::Ack(ack) => Some(ack.code),
+            _ => None,
+        }
+    }
+}
+
+impl AckCode {
+    pub fn value(&self) -> u64 {
+        match self {
+            Self::NotList => 1,
+            Self::Arg => 2,
+            Self::Password => 3,
+            Self::Permission => 4,
+            Self::Unknown => 5,
+            Self::NoExist => 50,
+            Self::PlaylistMax => 51,
+            Self::System => 52,
+            Self::PlaylistLoad => 53,
+            Self::UpdateAlready => 54,
+            Self::PlayerSync => 55,
+            Self::Exist => 56,
+            Self::Other(code) => *code,
+        }
+    }
+}
+
+impl From<u64> for AckCode {
+    fn from(code: u64) -> Self {
+        match code {
+            1 => Self::NotList,
+            2 => Self::Arg,
+            3 => Self::Password,
+            4 => Self::Permission,
+            5 => Self::Unknown,
+            50 => Self::NoExist,
+            51 => Self::PlaylistMax,
+            52 => Self::System,
+            53 => Self::PlaylistLoad,
+            54 => Self::UpdateAlready,
+            55 => Self::PlayerSync,
+            56 => Self::Exist,
+            code => Self::Other(code),
+        }
+    }
+}
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::Io(error) => write!(f, "io error: {error}"),
-            Self::Mpd(msg) => write!(f, "mpd error: {msg}"),
-            Self::MpdErrorResponse(code) => write!(f, "mpd returned error code {code}"),
+            Self::Io(_, msg) => write!(f, "io error: {msg}"),
+            Self::Ack(ack) => write!(f, "{ack}"),
+            Self::Protocol(msg) => write!(f, "mpd protocol error: {msg}"),
+            Self::InvalidResponse(msg) => write!(f, "invalid response from mpd: {msg}"),
             Self::InvalidQueue => write!(f, "queue error in mpd"),
+            Self::IncorrectPassword => write!(f, "incorrect password"),
             Self::SendError(error) => write!(f, "send to channel: {error}"),
             Self::Disconnect => write!(f, "connection to mpd was disconnected"),
         }
     }
 }
 
+impl fmt::Display for Ack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} [{}@{}", self.message, self.code.value(), self.index)?;
+        if let Some(command) = &self.command {
+            write!(f, " {command}")?;
+        }
+        write!(f, "]")
+    }
+}
+
 impl From<io::Error> for Error {
     fn from(error: io::Error) -> Self {
-        Self::Io(error.kind())
+        Self::Io(error.kind(), error.to_string())
     }
 }
 
 impl From<MpdProtocolError> for Error {
     fn from(error: MpdProtocolError) -> Self {
         match error {
-            MpdProtocolError::Io(ioerr) => Self::Io(ioerr.kind()),
-            _ => Self::Mpd(error.to_string()),
+            MpdProtocolError::Io(ioerr) => ioerr.into(),
+            _ => Self::Protocol(error.to_string()),
         }
     }
 }
 
 impl From<CommandError> for Error {
     fn from(error: CommandError) -> Self {
-        use mpd_client::protocol::response::Error as MpdErr;
         match error {
-            CommandError::ErrorResponse { error: MpdErr { code, .. }, .. }
-                => Self::MpdErrorResponse(code),
+            CommandError::ErrorResponse { error, .. } => Self::Ack(Ack {
+                code: error.code.into(),
+                message: error.message.into(),
+                command: error.current_command.map(Into::into),
+                index: error.command_index,
+            }),
 
-            _ => Self::Mpd(error.to_string()),
+            CommandError::ConnectionClosed => Self::Disconnect,
+            CommandError::Protocol(error) => error.into(),
+            CommandError::InvalidTypedResponse(error) => Self::InvalidResponse(error.to_string()),
         }
     }
 }
 
 impl From<ConnectionError> for Error {
     fn from(error: ConnectionError) -> Self {
-        Self::Mpd(error.to_string())
+        match error {
+            ConnectionError::Protocol(error) => error.into(),
+            _ => Self::Protocol(error.to_string()),
+        }
+    }
+}
+
+impl From<ConnectWithPasswordError> for Error {
+    fn from(error: ConnectWithPasswordError) -> Self {
+        match error {
+            ConnectWithPasswordError::ProtocolError(error) => error.into(),
+            ConnectWithPasswordError::IncorrectPassword => Self::IncorrectPassword,
+        }
     }
 }
 
@@ -68,3 +196,92 @@ impl From<mpsc::SendError> for Error {
         Self::SendError(error)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ack(code: u64) -> Error {
+        Error::Ack(Ack {
+            code: code.into(),
+            message: String::from("failed"),
+            command: Some(String::from("play")),
+            index: 0,
+        })
+    }
+
+    fn io(kind: io::ErrorKind) -> Error {
+        io::Error::from(kind).into()
+    }
+
+    fn closed_channel() -> mpsc::SendError {
+        let (mut tx, rx) = mpsc::channel::<()>(0);
+        drop(rx);
+        tx.try_send(()).unwrap_err().into_send_error()
+    }
+
+    #[test]
+    fn kinds() {
+        let table = [
+            (ack(1), ErrorKind::Fatal),
+            (ack(2), ErrorKind::Fatal),
+            (ack(3), ErrorKind::Permission),
+            (ack(4), ErrorKind::Permission),
+            (ack(5), ErrorKind::Fatal),
+            (ack(50), ErrorKind::Fatal),
+            (ack(51), ErrorKind::Fatal),
+            (ack(52), ErrorKind::Fatal),
+            (ack(53), ErrorKind::Fatal),
+            (ack(54), ErrorKind::Fatal),
+            (ack(55), ErrorKind::Fatal),
+            (ack(56), ErrorKind::Fatal),
+            (ack(99), ErrorKind::Fatal),
+            (io(io::ErrorKind::ConnectionRefused), ErrorKind::Retryable),
+            (io(io::ErrorKind::ConnectionReset), ErrorKind::Retryable),
+            (io(io::ErrorKind::BrokenPipe), ErrorKind::Retryable),
+            (io(io::ErrorKind::TimedOut), ErrorKind::Retryable),
+            (io(io::ErrorKind::NotFound), ErrorKind::Retryable),
+            (io(io::ErrorKind::InvalidInput), ErrorKind::Fatal),
+            (io(io::ErrorKind::Unsupported), ErrorKind::Fatal),
+            (Error::Protocol(String::from("garbage")), ErrorKind::Retryable),
+            (Error::Disconnect, ErrorKind::Retryable),
+            (Error::IncorrectPassword, ErrorKind::Permission),
+            (Error::InvalidResponse(String::from("bad")), ErrorKind::Fatal),
+            (Error::InvalidQueue, ErrorKind::Fatal),
+            (closed_channel().into(), ErrorKind::Fatal),
+        ];
+
+        for (error, kind) in table {
+            assert_eq!(error.kind(), kind, "{error:?}");
+        }
+    }
+
+    #[test]
+    fn command_errors() {
+        assert_eq!(Error::from(CommandError::ConnectionClosed).kind(), ErrorKind::Retryable);
+    }
+
+    #[test]
+    fn ack_codes() {
+        for code in [1, 2, 3, 4, 5, 50, 51, 52, 53, 54, 55, 56, 0, 42] {
+            assert_eq!(AckCode::from(code).value(), code);
+        }
+        assert_eq!(AckCode::from(50), AckCode::NoExist);
+        assert_eq!(AckCode::from(42), AckCode::Other(42));
+        assert_eq!(ack(4).ack_code(), Some(AckCode::Permission));
+        assert_eq!(Error::Disconnect.ack_code(), None);
+    }
+
+    #[test]
+    fn ack_display() {
+        assert_eq!(ack(50).to_string(), "failed [50@0 play]");
+
+        let ack = Ack {
+            code: AckCode::Arg,
+            message: String::from("bad"),
+            command: None,
+            index: 2,
+        };
+        assert_eq!(ack.to_string(), "bad [2@2]");
+    }
+}
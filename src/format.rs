@@ -0,0 +1,276 @@
+use std::{fmt, iter::Peekable, str::Chars};
+use mpd_client::tag::Tag;
+use serde::Deserialize;
+
+// template for showing a song, in the style of ncmpcpp.
+//
+// - `%tag%` is replaced by the value of a tag (like `%artist%` or
+//   `%albumartist%`), or by `%file%`, `%filename%` or `%duration%`.
+// - `{...}` is an optional section, it is left out if one of the
+//   placeholders in it has no value.
+// - `|` separates alternatives, the first one where all placeholders
+//   have a value is used.
+// - `\` escapes the next character, `%%` is a literal `%`.
+//
+// For example `{%artist% - }%title%|%file%` shows the artist (if there is
+// one) and the title, or the file name if the song has no title.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Format {
+    source: String,
+    alternatives: Vec<Vec<Node>>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Field(Field),
+    Optional(Vec<Vec<Node>>),
+}
+
+#[derive(Debug, Clone)]
+pub enum Field {
+    Tag(Tag),
+    File,
+    FileName,
+    Duration,
+}
+
+pub trait Fields {
+    fn field(&self, field: &Field) -> Option<String>;
+}
+
+impl Format {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut chars = source.chars().peekable();
+        let alternatives = parse_alternatives(&mut chars)?;
+
+        match chars.next() {
+            None => Ok(Self { source: source.to_owned(), alternatives }),
+            Some(_) => Err(format!("unmatched '}}' in format: {source}")),
+        }
+    }
+
+    pub fn render(&self, fields: &impl Fields) -> String {
+        render_alternatives(&self.alternatives, fields).unwrap_or_default()
+    }
+}
+
+impl TryFrom<String> for Format {
+    type Error = String;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        Self::parse(&source)
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl Field {
+    fn parse(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "file" => Ok(Field::File),
+            "filename" => Ok(Field::FileName),
+            "duration" => Ok(Field::Duration),
+            // MPD knows no other tags, so this is most likely a typo
+            _ => match Tag::try_from(name) {
+                Ok(Tag::Other(_)) => Err(format!("unknown placeholder in format: %{name}%")),
+                Ok(tag) => Ok(Field::Tag(tag)),
+                Err(_) => Err(format!("invalid placeholder in format: %{name}%")),
+            },
+        }
+    }
+}
+
+fn parse_alternatives(chars: &mut Peekable<Chars>) -> Result<Vec<Vec<Node>>, String> {
+    let mut alternatives = vec![parse_sequence(chars)?];
+    while chars.next_if_eq(&'|').is_some() {
+        alternatives.push(parse_sequence(chars)?);
+    }
+    Ok(alternatives)
+}
+
+fn parse_sequence(chars: &mut Peekable<Chars>) -> Result<Vec<Node>, String> {
+    let mut nodes = Vec::new();
+    let mut text = String::new();
+
+    while let Some(c) = chars.next_if(|c| *c != '|' && *c != '}') {
+        match c {
+            '\\' => text.extend(chars.next()),
+
+            '%' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('%') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(format!("unterminated placeholder: %{name}")),
+                    }
+                }
+
+                if name.is_empty() {
+                    text.push('%');
+                } else {
+                    if !text.is_empty() {
+                        nodes.push(Node::Text(std::mem::take(&mut text)));
+                    }
+                    nodes.push(Node::Field(Field::parse(&name)?));
+                }
+            }
+
+            '{' => {
+                if !text.is_empty() {
+                    nodes.push(Node::Text(std::mem::take(&mut text)));
+                }
+                let alternatives = parse_alternatives(chars)?;
+                if chars.next() != Some('}') {
+                    return Err(String::from("missing '}' in format"));
+                }
+                nodes.push(Node::Optional(alternatives));
+            }
+
+            c => text.push(c),
+        }
+    }
+
+    if !text.is_empty() {
+        nodes.push(Node::Text(text));
+    }
+    Ok(nodes)
+}
+
+fn render_alternatives(alternatives: &[Vec<Node>], fields: &impl Fields) -> Option<String> {
+    alternatives.iter().find_map(|nodes| render_sequence(nodes, fields))
+}
+
+fn render_sequence(nodes: &[Node], fields: &impl Fields) -> Option<String> {
+    let mut out = String::new();
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Field(field) => out.push_str(&fields.field(field).filter(|v| !v.is_empty())?),
+            Node::Optional(alternatives) => {
+                if let Some(text) = render_alternatives(alternatives, fields) {
+                    out.push_str(&text);
+                }
+            }
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+
+    struct Song(HashMap<Tag, &'static str>);
+
+    impl Fields for Song {
+        fn field(&self, field: &Field) -> Option<String> {
+            match field {
+                Field::Tag(tag) => self.0.get(tag).map(|v| v.to_string()),
+                _ => None,
+            }
+        }
+    }
+
+    fn song(tags: &[(Tag, &'static str)]) -> Song {
+        Song(tags.iter().cloned().collect())
+    }
+
+    fn render(format: &str, song: &Song) -> String {
+        Format::parse(format).unwrap().render(song)
+    }
+
+    #[test]
+    fn placeholders() {
+        let song = song(&[(Tag::Artist, "Artist"), (Tag::Title, "Title")]);
+        assert_eq!(render("%artist% - %title%", &song), "Artist - Title");
+        assert_eq!(render("%ARTIST%", &song), "Artist");
+        assert_eq!(render("plain text", &song), "plain text");
+        assert_eq!(render("", &song), "");
+    }
+
+    #[test]
+    fn missing_tags() {
+        let song = song(&[(Tag::Title, "Title")]);
+        // a missing placeholder outside of a section discards everything
+        assert_eq!(render("%artist% - %title%", &song), "");
+        assert_eq!(render("{%artist% - }%title%", &song), "Title");
+        assert_eq!(render("%file%", &song), "");
+        assert_eq!(render("%title%{ (%date%)}", &song), "Title");
+    }
+
+    #[test]
+    fn empty_values_are_missing() {
+        let song = song(&[(Tag::Artist, ""), (Tag::Title, "Title")]);
+        assert_eq!(render("{%artist% - }%title%", &song), "Title");
+    }
+
+    #[test]
+    fn alternatives() {
+        let titled = song(&[(Tag::Title, "Title"), (Tag::Name, "Stream")]);
+        let stream = song(&[(Tag::Name, "Stream")]);
+        let none = song(&[]);
+
+        assert_eq!(render("%title%|%name%", &titled), "Title");
+        assert_eq!(render("%title%|%name%", &stream), "Stream");
+        assert_eq!(render("%title%|%name%", &none), "");
+        assert_eq!(render("%title%|%name%|unknown", &none), "unknown");
+
+        assert_eq!(render("<{%title%|%name%}>", &stream), "<Stream>");
+        assert_eq!(render("<{%title%|%name%}>", &none), "<>");
+    }
+
+    #[test]
+    fn nesting() {
+        let format = "{%artist%{ (%date%)} - }%title%";
+        let full = song(&[(Tag::Artist, "A"), (Tag::Date, "1999"), (Tag::Title, "T")]);
+        let undated = song(&[(Tag::Artist, "A"), (Tag::Title, "T")]);
+        let anonymous = song(&[(Tag::Date, "1999"), (Tag::Title, "T")]);
+
+        assert_eq!(render(format, &full), "A (1999) - T");
+        assert_eq!(render(format, &undated), "A - T");
+        assert_eq!(render(format, &anonymous), "T");
+    }
+
+    #[test]
+    fn escaping() {
+        let song = song(&[(Tag::Title, "Title")]);
+        assert_eq!(render("100%% %title%", &song), "100% Title");
+        assert_eq!(render("\\{%title%\\}", &song), "{Title}");
+        assert_eq!(render("a \\| b", &song), "a | b");
+        assert_eq!(render("\\%title\\%", &song), "%title%");
+        assert_eq!(render("\\\\", &song), "\\");
+    }
+
+    #[test]
+    fn display_keeps_source() {
+        let source = "{%artist% - }%title%|%file%";
+        assert_eq!(Format::parse(source).unwrap().to_string(), source);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(Format::parse("%title").is_err());
+        assert!(Format::parse("%title% - %").is_err());
+        assert!(Format::parse("{%title%").is_err());
+        assert!(Format::parse("{{%title%}").is_err());
+        assert!(Format::parse("%title%}").is_err());
+        assert!(Format::parse("%title%}{").is_err());
+    }
+
+    #[test]
+    fn unknown_placeholders() {
+        assert!(Format::parse("%artsit%").is_err());
+        assert!(Format::parse("{%artsit% - }%title%").is_err());
+        assert!(Format::parse("%my tag%").is_err());
+        assert!(Format::parse("%musicbrainz_trackid%").is_ok());
+        assert!(Format::parse("%filename% %duration%").is_ok());
+    }
+}
@@ -1,18 +1,58 @@
+mod args;
+mod config;
+mod cover;
 mod error;
+mod format;
 mod mpd;
 mod app;
 
 use crate::app::App;
+use crate::args::Args;
+use crate::config::Config;
+use crate::mpd::MpdTarget;
 
 pub fn main() {
     tracing_subscriber::fmt()
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
         .init();
 
+    let args = Args::parse().unwrap_or_else(|error| {
+        eprintln!("{error}\n\n{}", Args::USAGE);
+        std::process::exit(2);
+    });
+
+    if args.help {
+        println!("{}", Args::USAGE);
+        return;
+    }
+
+    let config_path = args.config.clone().or_else(Config::default_path);
+    let config = match &config_path {
+        Some(path) => Config::load(path).unwrap_or_else(|error| {
+            eprintln!("error in configuration: {error}");
+            std::process::exit(2);
+        }),
+        None => Config::default(),
+    };
+
+    // a chosen profile takes precedence over MPD_HOST and MPD_PORT
+    let profile = args.profile.clone().or(config.default_profile.clone());
+    let target = config.connection(profile.as_deref())
+        .and_then(|connection| MpdTarget::resolve(
+            args.host.as_deref(),
+            args.port,
+            connection,
+            profile.is_none(),
+        ))
+        .unwrap_or_else(|error| {
+            eprintln!("{error}");
+            std::process::exit(2);
+        });
+
     if let Err(error) = iced::application(App::title, App::update, App::view)
         .subscription(App::subscriptions)
-        .theme(|_| iced::Theme::KanagawaDragon)
-        .run_with(App::new)
+        .theme(App::theme)
+        .run_with(move || App::new(target, profile, config, config_path))
     {
         tracing::error!("error running iced runtime: {error}");
         std::process::exit(1);
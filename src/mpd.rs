@@ -1,17 +1,23 @@
+mod commands;
 mod mpd_ctrl;
 mod mpd_events;
+mod mpd_target;
+mod permissions;
 
 use iced::Task;
 
 use crate::error::Error;
 pub use mpd_events::MpdEvent;
-pub use mpd_ctrl::{MpdCtrl, Cmd, CmdResult};
+pub use mpd_ctrl::{MpdCtrl, Cmd, CmdResult, AddMode};
+pub use commands::{DirEntry, Output, MixRamp, AudioStatus};
+pub use mpd_target::MpdTarget;
+pub use permissions::Permissions;
 
-pub fn mpd_connect() -> Task<Result<MpdEvent, Error>> {
-    Task::stream(iced::stream::try_channel(1, |tx| async {
-        mpd_events::MpdEvents::open()
+pub fn mpd_connect(target: MpdTarget, binary_limit: usize) -> Task<Result<MpdEvent, Error>> {
+    Task::stream(iced::stream::try_channel(1, move |tx| async move {
+        mpd_events::MpdEvents::open(&target)
             .await?
-            .run(tx)
+            .run(binary_limit, tx)
             .await
     }))
 }
@@ -0,0 +1,624 @@
+// commands which are not provided by `mpd_client`.
+
+use std::{collections::BTreeMap, time::Duration};
+use mpd_client::{
+    commands::{Command, Find},
+    filter::Filter,
+    protocol::{
+        command::Command as RawCommand,
+        response::Frame,
+    },
+    responses::{Song, TypedResponseError},
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct AllowedCommands;
+
+impl Command for AllowedCommands {
+    type Response = Vec<String>;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("commands")
+    }
+
+    fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(values(frame, "command"))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DeniedCommands;
+
+impl Command for DeniedCommands {
+    type Response = Vec<String>;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("notcommands")
+    }
+
+    fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(values(frame, "command"))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SwitchPartition<'a>(pub &'a str);
+
+impl Command for SwitchPartition<'_> {
+    type Response = ();
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("partition").argument(self.0)
+    }
+
+    fn response(self, _: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MixRamp {
+    pub db: f32,
+    pub delay: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MixRampStatus;
+
+impl Command for MixRampStatus {
+    type Response = MixRamp;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("status")
+    }
+
+    fn response(self, mut frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        let db = frame.get("mixrampdb")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.0);
+
+        // a missing or negative (nan) delay disables MixRamp
+        let delay = frame.get("mixrampdelay")
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| *v > 0.0)
+            .map(Duration::from_secs_f64);
+
+        Ok(MixRamp { db, delay })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AudioStatus {
+    pub format: Option<String>,
+    pub bitrate: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GetAudioStatus;
+
+impl Command for GetAudioStatus {
+    type Response = AudioStatus;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("status")
+    }
+
+    fn response(self, mut frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(AudioStatus {
+            format: frame.get("audio"),
+            bitrate: frame.get("bitrate").and_then(|v| v.parse().ok()),
+        })
+    }
+}
+
+// `config` command: the music directory, only allowed for clients
+// connected over a local socket.
+#[derive(Debug, Clone, Copy)]
+pub struct MusicDirectory;
+
+impl Command for MusicDirectory {
+    type Response = Option<String>;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("config")
+    }
+
+    fn response(self, mut frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(frame.get("music_directory"))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum SetMixRamp {
+    Db(f32),
+    Delay(Option<Duration>),
+}
+
+impl Command for SetMixRamp {
+    type Response = ();
+
+    fn command(&self) -> RawCommand {
+        match *self {
+            SetMixRamp::Db(db) => RawCommand::new("mixrampdb")
+                .argument(db.to_string()),
+
+            SetMixRamp::Delay(Some(delay)) => RawCommand::new("mixrampdelay")
+                .argument(delay.as_secs_f32().to_string()),
+
+            SetMixRamp::Delay(None) => RawCommand::new("mixrampdelay")
+                .argument("nan"),
+        }
+    }
+
+    fn response(self, _: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ListPartitions;
+
+impl Command for ListPartitions {
+    type Response = Vec<String>;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("listpartitions")
+    }
+
+    fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(values(frame, "partition"))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewPartition<'a>(pub &'a str);
+
+impl Command for NewPartition<'_> {
+    type Response = ();
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("newpartition").argument(self.0)
+    }
+
+    fn response(self, _: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeletePartition<'a>(pub &'a str);
+
+impl Command for DeletePartition<'_> {
+    type Response = ();
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("delpartition").argument(self.0)
+    }
+
+    fn response(self, _: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MoveOutput<'a>(pub &'a str);
+
+impl Command for MoveOutput<'_> {
+    type Response = ();
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("moveoutput").argument(self.0)
+    }
+
+    fn response(self, _: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(())
+    }
+}
+
+// `add` command: add a song or a whole directory (recursively).
+//
+// Unlike `addid` (`mpd_client::commands::Add`) this accepts directories.
+#[derive(Debug, Clone)]
+pub struct AddUri<'a> {
+    pub uri: &'a str,
+    pub after_current: Option<usize>,
+}
+
+impl Command for AddUri<'_> {
+    type Response = ();
+
+    fn command(&self) -> RawCommand {
+        let command = RawCommand::new("add").argument(self.uri);
+        match self.after_current {
+            Some(delta) => command.argument(format!("+{delta}")),
+            None => command,
+        }
+    }
+
+    fn response(self, _: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum DirEntry {
+    Directory(String),
+    Song {
+        file: String,
+        title: Option<String>,
+        artist: Option<String>,
+        duration: Option<Duration>,
+    },
+    Playlist(String),
+}
+
+impl DirEntry {
+    pub fn path(&self) -> &str {
+        match self {
+            DirEntry::Directory(path) => path,
+            DirEntry::Song { file, .. } => file,
+            DirEntry::Playlist(path) => path,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        let path = self.path();
+        path.rsplit('/').next().unwrap_or(path)
+    }
+}
+
+// `lsinfo` command: list the content of a directory.
+//
+// `mpd_client` only provides the songs of a listing, so this also keeps
+// the subdirectories and playlist files.
+#[derive(Debug, Clone)]
+pub struct ListInfo<'a>(pub &'a str);
+
+impl Command for ListInfo<'_> {
+    type Response = Vec<DirEntry>;
+
+    fn command(&self) -> RawCommand {
+        if self.0.is_empty() {
+            RawCommand::new("lsinfo")
+        } else {
+            RawCommand::new("lsinfo").argument(self.0)
+        }
+    }
+
+    fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        let mut entries = Vec::new();
+
+        for (key, value) in frame {
+            match key.as_ref() {
+                "directory" => entries.push(DirEntry::Directory(value)),
+                "playlist" => entries.push(DirEntry::Playlist(value)),
+                "file" => entries.push(DirEntry::Song {
+                    file: value,
+                    title: None,
+                    artist: None,
+                    duration: None,
+                }),
+
+                key => if let Some(DirEntry::Song { title, artist, duration, .. }) = entries.last_mut() {
+                    match key {
+                        "Title" => *title = Some(value),
+                        "Artist" if artist.is_none() => *artist = Some(value),
+                        "duration" => *duration = value
+                            .parse()
+                            .ok()
+                            .map(Duration::from_secs_f64),
+                        _ => (),
+                    }
+                },
+            }
+        }
+
+        Ok(entries)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Search {
+    pub filter: Filter,
+    pub limit: usize,
+}
+
+impl Command for Search {
+    type Response = Vec<Song>;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("search")
+            .argument(&self.filter)
+            .argument("window")
+            .argument(format!("0:{}", self.limit))
+    }
+
+    fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        // the response is the same as the one of `find`
+        Find::new(self.filter).response(frame)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Output {
+    pub id: u32,
+    pub name: String,
+    pub plugin: String,
+    pub enabled: bool,
+    pub attributes: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Outputs;
+
+impl Command for Outputs {
+    type Response = Vec<Output>;
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("outputs")
+    }
+
+    fn response(self, frame: Frame) -> Result<Self::Response, TypedResponseError> {
+        let mut outputs: Vec<Output> = Vec::new();
+
+        for (key, value) in frame {
+            if key.as_ref() == "outputid" {
+                let id = value
+                    .parse()
+                    .map_err(|_| TypedResponseError::invalid_value("outputid", value))?;
+                outputs.push(Output {
+                    id,
+                    name: String::new(),
+                    plugin: String::new(),
+                    enabled: false,
+                    attributes: BTreeMap::new(),
+                });
+                continue;
+            }
+
+            let Some(output) = outputs.last_mut() else {
+                return Err(TypedResponseError::unexpected_field("outputid", key.as_ref()));
+            };
+
+            match key.as_ref() {
+                "outputname" => output.name = value,
+                "plugin" => output.plugin = value,
+                "outputenabled" => output.enabled = value == "1",
+                "attribute" => if let Some((name, value)) = value.split_once('=') {
+                    output.attributes.insert(name.to_owned(), value.to_owned());
+                },
+                _ => (),
+            }
+        }
+
+        Ok(outputs)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum SetOutput {
+    Enable(u32),
+    Disable(u32),
+    Toggle(u32),
+}
+
+impl Command for SetOutput {
+    type Response = ();
+
+    fn command(&self) -> RawCommand {
+        match *self {
+            SetOutput::Enable(id) => RawCommand::new("enableoutput").argument(id.to_string()),
+            SetOutput::Disable(id) => RawCommand::new("disableoutput").argument(id.to_string()),
+            SetOutput::Toggle(id) => RawCommand::new("toggleoutput").argument(id.to_string()),
+        }
+    }
+
+    fn response(self, _: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SetOutputAttribute<'a> {
+    pub id: u32,
+    pub name: &'a str,
+    pub value: &'a str,
+}
+
+impl Command for SetOutputAttribute<'_> {
+    type Response = ();
+
+    fn command(&self) -> RawCommand {
+        RawCommand::new("outputset")
+            .argument(self.id.to_string())
+            .argument(self.name)
+            .argument(self.value)
+    }
+
+    fn response(self, _: Frame) -> Result<Self::Response, TypedResponseError> {
+        Ok(())
+    }
+}
+
+fn values(frame: Frame, key: &str) -> Vec<String> {
+    frame.into_iter()
+        .filter(|(k, _)| k.as_ref() == key)
+        .map(|(_, v)| v)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, io::{self, Read, Write}};
+    use mpd_client::protocol::Connection;
+    use super::*;
+
+    // a server which sends its greeting and then a recorded response,
+    // each in one read.
+    struct Recorded(VecDeque<Vec<u8>>);
+
+    impl Read for Recorded {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let Some(data) = self.0.pop_front() else {
+                return Ok(0);
+            };
+            buf[..data.len()].copy_from_slice(&data);
+            Ok(data.len())
+        }
+    }
+
+    impl Write for Recorded {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn run<C: Command>(command: C, response: &str) -> Result<C::Response, TypedResponseError> {
+        let recorded = Recorded(VecDeque::from([
+            b"OK MPD 0.23.5\n".to_vec(),
+            response.as_bytes().to_vec(),
+        ]));
+        let mut connection = Connection::connect(recorded).unwrap();
+        let frame = connection
+            .command(command.command())
+            .unwrap()
+            .into_single_frame()
+            .unwrap();
+
+        command.response(frame)
+    }
+
+    #[test]
+    fn list_info_empty() {
+        assert!(run(ListInfo(""), "OK\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn list_info() {
+        let entries = run(ListInfo("Music"), "\
+directory: Music/Album
+Last-Modified: 2024-02-11T10:20:30Z
+file: Music/Album/01 First.flac
+Last-Modified: 2024-02-11T10:20:30Z
+Format: 44100:16:2
+Artist: First Artist
+Artist: Second Artist
+AlbumArtist: Someone
+Title: First
+Track: 1
+Time: 215
+duration: 214.520
+file: Music/untagged.mp3
+Last-Modified: 2023-01-01T00:00:00Z
+Time: 60
+duration: not a number
+playlist: Music/mix.m3u
+Last-Modified: 2022-05-05T05:05:05Z
+OK
+").unwrap();
+
+        assert_eq!(entries.len(), 4);
+        assert!(matches!(&entries[0], DirEntry::Directory(path) if path == "Music/Album"));
+        assert_eq!(entries[0].name(), "Album");
+
+        let DirEntry::Song { file, title, artist, duration } = &entries[1] else {
+            panic!("not a song: {:?}", entries[1]);
+        };
+        assert_eq!(file, "Music/Album/01 First.flac");
+        assert_eq!(title.as_deref(), Some("First"));
+        // only the first of several artists
+        assert_eq!(artist.as_deref(), Some("First Artist"));
+        assert_eq!(*duration, Some(Duration::from_secs_f64(214.52)));
+
+        let DirEntry::Song { title, artist, duration, .. } = &entries[2] else {
+            panic!("not a song: {:?}", entries[2]);
+        };
+        assert_eq!(*title, None);
+        assert_eq!(*artist, None);
+        assert_eq!(*duration, None);
+        assert_eq!(entries[2].name(), "untagged.mp3");
+
+        assert!(matches!(&entries[3], DirEntry::Playlist(path) if path == "Music/mix.m3u"));
+    }
+
+    #[test]
+    fn list_info_ignores_leading_fields() {
+        // fields before the first entry belong to nothing
+        let entries = run(ListInfo(""), "Title: stray\ndirectory: a\nOK\n").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path(), "a");
+    }
+
+    #[test]
+    fn outputs_empty() {
+        assert!(run(Outputs, "OK\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn outputs() {
+        let outputs = run(Outputs, "\
+outputid: 0
+outputname: PipeWire
+plugin: pipewire
+outputenabled: 1
+attribute: dop=0
+attribute: allowed_formats=
+outputid: 1
+outputname: My HTTP Stream
+plugin: httpd
+outputenabled: 0
+mixer: none
+outputid: 2
+outputname: Broken
+plugin: alsa
+outputenabled: 1
+attribute: no separator
+OK
+").unwrap();
+
+        assert_eq!(outputs.len(), 3);
+
+        assert_eq!(outputs[0].id, 0);
+        assert_eq!(outputs[0].name, "PipeWire");
+        assert_eq!(outputs[0].plugin, "pipewire");
+        assert!(outputs[0].enabled);
+        assert_eq!(outputs[0].attributes.get("dop").map(String::as_str), Some("0"));
+        assert_eq!(outputs[0].attributes.get("allowed_formats").map(String::as_str), Some(""));
+
+        // unknown keys are skipped
+        assert_eq!(outputs[1].id, 1);
+        assert_eq!(outputs[1].name, "My HTTP Stream");
+        assert!(!outputs[1].enabled);
+        assert!(outputs[1].attributes.is_empty());
+
+        // attributes without a value are dropped
+        assert!(outputs[2].attributes.is_empty());
+    }
+
+    #[test]
+    fn outputs_malformed() {
+        assert!(run(Outputs, "outputid: first\nOK\n").is_err());
+        assert!(run(Outputs, "outputname: orphan\noutputid: 0\nOK\n").is_err());
+    }
+
+    #[test]
+    fn commands() {
+        let allowed = run(AllowedCommands, "command: add\ncommand: play\nOK\n").unwrap();
+        assert_eq!(allowed, ["add", "play"]);
+        assert!(run(DeniedCommands, "OK\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn partitions() {
+        assert_eq!(run(ListPartitions, "partition: default\nOK\n").unwrap(), ["default"]);
+        assert_eq!(
+            run(ListPartitions, "partition: default\npartition: office\npartition: living room\nOK\n")
+                .unwrap(),
+            ["default", "office", "living room"],
+        );
+    }
+}
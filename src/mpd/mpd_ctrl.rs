@@ -1,14 +1,36 @@
-use std::time::Duration;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 use bytes::BytesMut;
 use mpd_client::{
     Client,
+    client::CommandError,
+    commands::{Command, SongId, SongPosition, SingleMode, ReplayGainMode},
+    filter::Filter,
+    protocol::command::{Command as RawCommand, CommandList as RawCommandList},
     responses::{
+        AlbumArt as AlbumArtResponse,
+        Count,
+        Stats,
         Status,
+        Song,
         SongInQueue,
-    }
+    },
+    tag::Tag,
 };
 
 use crate::error::Error;
+use super::{
+    commands::{
+        AllowedCommands, DeniedCommands, AddUri, ListInfo, DirEntry, Search,
+        Outputs, Output, SetOutput, SetOutputAttribute,
+        ListPartitions, SwitchPartition, NewPartition, DeletePartition, MoveOutput,
+        MixRamp, MixRampStatus, SetMixRamp, AudioStatus, GetAudioStatus,
+    },
+    Permissions,
+};
 
 #[derive(Debug, Clone)]
 pub enum Cmd {
@@ -23,23 +45,108 @@ pub enum Cmd {
     SkipForward(Duration),
     SkipBackward(Duration),
     Seek(Duration),
+    PlayId(SongId),
+    Delete(Vec<SongId>),
+    Move(Vec<(SongId, SongPosition)>),
+    Crop,
+    ClearQueue,
+    Add(Vec<String>, AddMode),
+    Update(Option<String>),
+    Rescan(Option<String>),
+    LoadPlaylist(String, AddMode),
+    SavePlaylist(String),
+    RenamePlaylist(String, String),
+    DeletePlaylist(String),
+    PlaylistAdd(String, Vec<String>),
+    PlaylistDelete(String, Vec<usize>),
+    PlaylistMove(String, usize, usize),
+    EnableOutput(u32),
+    DisableOutput(u32),
+    ToggleOutput(u32),
+    SetOutputAttribute(u32, String, String),
+    SwitchPartition(String),
+    NewPartition(String),
+    DeletePartition(String),
+    MoveOutput(String),
+    SetSingle(SingleMode),
+    SetCrossfade(Duration),
+    SetMixRampDb(f32),
+    SetMixRampDelay(Option<Duration>),
+    SetReplayGain(ReplayGainMode),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddMode {
+    Append,
+    AfterCurrent,
+    Replace,
+}
+
+impl Cmd {
+    pub fn mpd_command(&self) -> &'static str {
+        match self {
+            Cmd::Play => "play",
+            Cmd::Pause => "pause",
+            Cmd::Prev => "previous",
+            Cmd::Next => "next",
+            Cmd::SetVolume(_) => "setvol",
+            Cmd::SetRandom(_) => "random",
+            Cmd::SetRepeat(_) => "repeat",
+            Cmd::SetConsume(_) => "consume",
+            Cmd::SkipForward(_) => "seekcur",
+            Cmd::SkipBackward(_) => "seekcur",
+            Cmd::Seek(_) => "seekcur",
+            Cmd::PlayId(_) => "playid",
+            Cmd::Delete(_) => "deleteid",
+            Cmd::Move(_) => "moveid",
+            Cmd::Crop => "deleteid",
+            Cmd::ClearQueue => "clear",
+            Cmd::Add(..) => "add",
+            Cmd::Update(_) => "update",
+            Cmd::Rescan(_) => "rescan",
+            Cmd::LoadPlaylist(..) => "load",
+            Cmd::SavePlaylist(_) => "save",
+            Cmd::RenamePlaylist(..) => "rename",
+            Cmd::DeletePlaylist(_) => "rm",
+            Cmd::PlaylistAdd(..) => "playlistadd",
+            Cmd::PlaylistDelete(..) => "playlistdelete",
+            Cmd::PlaylistMove(..) => "playlistmove",
+            Cmd::EnableOutput(_) => "enableoutput",
+            Cmd::DisableOutput(_) => "disableoutput",
+            Cmd::ToggleOutput(_) => "toggleoutput",
+            Cmd::SetOutputAttribute(..) => "outputset",
+            Cmd::SwitchPartition(_) => "partition",
+            Cmd::NewPartition(_) => "newpartition",
+            Cmd::DeletePartition(_) => "delpartition",
+            Cmd::MoveOutput(_) => "moveoutput",
+            Cmd::SetSingle(_) => "single",
+            Cmd::SetCrossfade(_) => "crossfade",
+            Cmd::SetMixRampDb(_) => "mixrampdb",
+            Cmd::SetMixRampDelay(_) => "mixrampdelay",
+            Cmd::SetReplayGain(_) => "replay_gain_mode",
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct CmdResult {
     pub cmd: Cmd,
-    pub error: Option<String>,
+    pub error: Option<Error>,
 }
 
 #[derive(Clone, Debug)]
 pub struct MpdCtrl {
     client: Client,
+    // second connection for cover art and large database reads, so
+    // commands of the user never wait behind them.
+    bulk: Client,
+    music_dir: Option<Arc<Path>>,
 }
 
 
 impl MpdCtrl {
-    pub fn new(client: Client) -> Self {
-        Self { client }
+    pub fn new(client: Client, bulk: Client, music_dir: Option<PathBuf>) -> Self {
+        Self { client, bulk, music_dir: music_dir.map(Arc::from) }
     }
 
     pub async fn command(&self, cmd: Cmd) -> CmdResult {
@@ -125,9 +232,325 @@ impl MpdCtrl {
                     .await
                     .err()
             }
+
+            Cmd::PlayId(id) => {
+                self.client
+                    .command(commands::Play::song(id))
+                    .await
+                    .err()
+            }
+
+            Cmd::Delete(ref ids) => {
+                let list: Vec<_> = ids.iter()
+                    .map(|id| commands::Delete::id(*id))
+                    .collect();
+                self.client
+                    .command_list(list)
+                    .await
+                    .err()
+            }
+
+            Cmd::Move(ref moves) => {
+                let list: Vec<_> = moves.iter()
+                    .map(|(id, pos)| commands::Move::id(*id).to_position(*pos))
+                    .collect();
+                self.client
+                    .command_list(list)
+                    .await
+                    .err()
+            }
+
+            Cmd::Crop => {
+                self.crop()
+                    .await
+                    .err()
+            }
+
+            Cmd::ClearQueue => {
+                self.client
+                    .command(commands::ClearQueue)
+                    .await
+                    .err()
+            }
+
+            Cmd::Add(ref uris, mode) => {
+                self.add(uris, mode)
+                    .await
+                    .err()
+            }
+
+            Cmd::Update(ref path) => {
+                let update = match path {
+                    Some(path) => commands::Update::new().uri(path),
+                    None => commands::Update::new(),
+                };
+                self.client
+                    .command(update)
+                    .await
+                    .err()
+            }
+
+            Cmd::Rescan(ref path) => {
+                let rescan = match path {
+                    Some(path) => commands::Rescan::new().uri(path),
+                    None => commands::Rescan::new(),
+                };
+                self.client
+                    .command(rescan)
+                    .await
+                    .err()
+            }
+
+            Cmd::LoadPlaylist(ref name, mode) => {
+                self.load(name, mode)
+                    .await
+                    .err()
+            }
+
+            Cmd::SavePlaylist(ref name) => {
+                self.client
+                    .command(commands::SaveQueueAsPlaylist(name))
+                    .await
+                    .err()
+            }
+
+            Cmd::RenamePlaylist(ref from, ref to) => {
+                self.client
+                    .command(commands::RenamePlaylist::new(from, to))
+                    .await
+                    .err()
+            }
+
+            Cmd::DeletePlaylist(ref name) => {
+                self.client
+                    .command(commands::DeletePlaylist(name))
+                    .await
+                    .err()
+            }
+
+            Cmd::PlaylistAdd(ref name, ref uris) => {
+                let list: Vec<_> = uris.iter()
+                    .map(|uri| commands::AddToPlaylist::new(name, uri))
+                    .collect();
+                self.client
+                    .command_list(list)
+                    .await
+                    .err()
+            }
+
+            Cmd::PlaylistDelete(ref name, ref positions) => {
+                // remove from the back, so the other positions stay valid
+                let mut positions = positions.clone();
+                positions.sort_unstable_by(|a, b| b.cmp(a));
+                let list: Vec<_> = positions.into_iter()
+                    .map(|pos| commands::RemoveFromPlaylist::position(name, pos))
+                    .collect();
+                self.client
+                    .command_list(list)
+                    .await
+                    .err()
+            }
+
+            Cmd::PlaylistMove(ref name, from, to) => {
+                self.client
+                    .command(commands::MoveInPlaylist::new(name, from, to))
+                    .await
+                    .err()
+            }
+
+            Cmd::EnableOutput(id) => {
+                self.client
+                    .command(SetOutput::Enable(id))
+                    .await
+                    .err()
+            }
+
+            Cmd::DisableOutput(id) => {
+                self.client
+                    .command(SetOutput::Disable(id))
+                    .await
+                    .err()
+            }
+
+            Cmd::ToggleOutput(id) => {
+                self.client
+                    .command(SetOutput::Toggle(id))
+                    .await
+                    .err()
+            }
+
+            Cmd::SetOutputAttribute(id, ref name, ref value) => {
+                self.client
+                    .command(SetOutputAttribute { id, name, value })
+                    .await
+                    .err()
+            }
+
+            Cmd::SwitchPartition(ref name) => {
+                self.client
+                    .command(SwitchPartition(name))
+                    .await
+                    .err()
+            }
+
+            Cmd::NewPartition(ref name) => {
+                self.client
+                    .command(NewPartition(name))
+                    .await
+                    .err()
+            }
+
+            Cmd::DeletePartition(ref name) => {
+                self.client
+                    .command(DeletePartition(name))
+                    .await
+                    .err()
+            }
+
+            Cmd::MoveOutput(ref name) => {
+                self.client
+                    .command(MoveOutput(name))
+                    .await
+                    .err()
+            }
+
+            Cmd::SetSingle(mode) => {
+                self.client
+                    .command(commands::SetSingle(mode))
+                    .await
+                    .err()
+            }
+
+            Cmd::SetCrossfade(d) => {
+                self.client
+                    .command(commands::Crossfade(d))
+                    .await
+                    .err()
+            }
+
+            Cmd::SetMixRampDb(db) => {
+                self.client
+                    .command(SetMixRamp::Db(db))
+                    .await
+                    .err()
+            }
+
+            Cmd::SetMixRampDelay(delay) => {
+                self.client
+                    .command(SetMixRamp::Delay(delay))
+                    .await
+                    .err()
+            }
+
+            Cmd::SetReplayGain(mode) => {
+                self.client
+                    .command(commands::SetReplayGainMode(mode))
+                    .await
+                    .err()
+            }
+        };
+
+        CmdResult { cmd, error: error.map(Error::from) }
+    }
+
+    async fn crop(&self) -> Result<(), CommandError> {
+        use mpd_client::commands;
+
+        let (status, queue) = self.client
+            .command_list((commands::Status, commands::Queue))
+            .await?;
+        let Some((_, current)) = status.current_song else {
+            tracing::info!("no current song, nothing to crop");
+            return Ok(());
         };
 
-        CmdResult { cmd, error: error.map(|e| e.to_string()) }
+        // by id, so songs moved or played meanwhile by another client are
+        // still the right ones
+        let list: Vec<_> = queue
+            .into_iter()
+            .map(|song| song.id)
+            .filter(|id| *id != current)
+            .map(commands::Delete::id)
+            .collect();
+
+        if list.is_empty() {
+            return Ok(());
+        }
+        self.client.command_list(list).await
+            .map(|_| ())
+    }
+
+    async fn add(&self, uris: &[String], mode: AddMode) -> Result<(), CommandError> {
+        use mpd_client::commands::Command;
+
+        if uris.is_empty() {
+            return Ok(());
+        }
+
+        let insert = self.can_insert(mode).await?;
+        let adds = uris.iter()
+            .enumerate()
+            .map(|(i, uri)| AddUri { uri, after_current: insert.then_some(i) }.command())
+            .collect();
+
+        self.run_add(adds, mode).await
+    }
+
+    async fn load(&self, name: &str, mode: AddMode) -> Result<(), CommandError> {
+        use mpd_client::commands::{self, Command};
+
+        let load = commands::LoadPlaylist::name(name).command();
+        let load = if self.can_insert(mode).await? {
+            load.argument("0:").argument("+0")
+        } else {
+            load
+        };
+
+        self.run_add(vec![load], mode).await
+    }
+
+    // whether songs can be inserted after the current one, relative
+    // positions need a current song, otherwise songs are appended.
+    async fn can_insert(&self, mode: AddMode) -> Result<bool, CommandError> {
+        use mpd_client::commands;
+
+        match mode {
+            AddMode::AfterCurrent => Ok(self.client
+                .command(commands::Status)
+                .await?
+                .current_song
+                .is_some()),
+            _ => Ok(false),
+        }
+    }
+
+    async fn run_add(&self, adds: Vec<RawCommand>, mode: AddMode) -> Result<(), CommandError> {
+        use mpd_client::commands::{self, Command};
+
+        let mut list = Vec::new();
+        if mode == AddMode::Replace {
+            list.push(commands::ClearQueue.command());
+        }
+        list.extend(adds);
+        if mode == AddMode::Replace {
+            list.push(commands::Play::song(SongPosition(0)).command());
+        }
+
+        self.run_list(list).await
+    }
+
+    async fn run_list(&self, commands: Vec<RawCommand>) -> Result<(), CommandError> {
+        let mut commands = commands.into_iter();
+        let Some(first) = commands.next() else {
+            return Ok(());
+        };
+
+        let mut list = RawCommandList::new(first);
+        list.extend(commands);
+        self.client
+            .raw_command_list(list)
+            .await
+            .map(|_| ())
     }
 
     pub async fn get_status(&self) -> Result<Status, Error> {
@@ -137,6 +560,29 @@ impl MpdCtrl {
             .map_err(Error::from)
     }
 
+    pub async fn get_audio_status(&self) -> Result<AudioStatus, Error> {
+        self.client
+            .command(GetAudioStatus)
+            .await
+            .map_err(Error::from)
+    }
+
+    pub async fn get_modes(&self) -> Result<(MixRamp, ReplayGainMode), Error> {
+        self.client
+            .command_list((MixRampStatus, mpd_client::commands::ReplayGainStatus))
+            .await
+            .map(|(mixramp, replay_gain)| (mixramp, replay_gain.mode))
+            .map_err(Error::from)
+    }
+
+    pub async fn get_permissions(&self) -> Result<Permissions, Error> {
+        self.client
+            .command_list((AllowedCommands, DeniedCommands))
+            .await
+            .map(|(allowed, denied)| Permissions::new(allowed, denied))
+            .map_err(Error::from)
+    }
+
     pub async fn get_queue(&self) -> Result<Vec<SongInQueue>, Error> {
         self.client
             .command(mpd_client::commands::Queue::all())
@@ -144,11 +590,133 @@ impl MpdCtrl {
             .map_err(Error::from)
     }
 
-    pub async fn get_cover_art(&self, uri: &str) -> Result<Option<BytesMut>, Error> {
+    pub async fn list(&self, tag: Tag, filter: Option<Filter>) -> Result<Vec<String>, Error> {
+        let list = mpd_client::commands::List::new(tag);
+        let list = match filter {
+            Some(filter) => list.filter(filter),
+            None => list,
+        };
+
+        self.bulk
+            .command(list)
+            .await
+            .map(|values| values
+                .into_raw_values()
+                .into_iter()
+                .map(|(_, value)| value)
+                .collect())
+            .map_err(Error::from)
+    }
+
+    pub async fn find(&self, filter: Filter) -> Result<Vec<Song>, Error> {
+        self.bulk
+            .command(mpd_client::commands::Find::new(filter))
+            .await
+            .map_err(Error::from)
+    }
+
+    pub async fn search(&self, filter: Filter, limit: usize) -> Result<Vec<Song>, Error> {
+        self.bulk
+            .command(Search { filter, limit })
+            .await
+            .map_err(Error::from)
+    }
+
+    pub async fn get_stats(&self) -> Result<Stats, Error> {
+        self.client
+            .command(mpd_client::commands::Stats)
+            .await
+            .map_err(Error::from)
+    }
+
+    pub async fn count_grouped(&self, tag: Tag) -> Result<Vec<(String, Count)>, Error> {
+        self.bulk
+            .command(mpd_client::commands::CountGrouped::new(tag))
+            .await
+            .map_err(Error::from)
+    }
+
+    pub async fn get_playlists(&self) -> Result<Vec<String>, Error> {
+        self.client
+            .command(mpd_client::commands::GetPlaylists)
+            .await
+            .map(|list| list.into_iter().map(|p| p.name).collect())
+            .map_err(Error::from)
+    }
+
+    pub async fn get_playlist(&self, name: &str) -> Result<Vec<Song>, Error> {
+        self.bulk
+            .command(mpd_client::commands::GetPlaylist(name))
+            .await
+            .map_err(Error::from)
+    }
+
+    pub async fn get_partitions(&self) -> Result<Vec<String>, Error> {
+        self.client
+            .command(ListPartitions)
+            .await
+            .map_err(Error::from)
+    }
+
+    pub async fn get_outputs(&self) -> Result<Vec<Output>, Error> {
         self.client
-            .album_art(uri)
+            .command(Outputs)
             .await
-            .map(|opt| opt.map(|x| x.0))
             .map_err(Error::from)
     }
+
+    pub async fn list_dir(&self, path: &str) -> Result<Vec<DirEntry>, Error> {
+        self.bulk
+            .command(ListInfo(path))
+            .await
+            .map_err(Error::from)
+    }
+
+    pub async fn get_album_art(&self, uri: &str) -> Result<Option<BytesMut>, Error> {
+        use mpd_client::commands::AlbumArt;
+
+        self.read_binary(|offset| AlbumArt::new(uri).offset(offset))
+            .await
+            .map_err(Error::from)
+    }
+
+    pub async fn get_embedded_picture(&self, uri: &str) -> Result<Option<BytesMut>, Error> {
+        use mpd_client::commands::AlbumArtEmbedded;
+
+        self.read_binary(|offset| AlbumArtEmbedded::new(uri).offset(offset))
+            .await
+            .map_err(Error::from)
+    }
+
+    pub fn music_directory(&self) -> Option<&Path> {
+        self.music_dir.as_deref()
+    }
+
+    async fn read_binary<C>(
+        &self,
+        command: impl Fn(usize) -> C,
+    ) -> Result<Option<BytesMut>, CommandError>
+    where
+        C: Command<Response = Option<AlbumArtResponse>>,
+    {
+        let Some(first) = self.bulk.command(command(0)).await? else {
+            return Ok(None);
+        };
+
+        let mut data = first.data;
+        data.reserve(first.size.saturating_sub(data.len()));
+
+        while data.len() < first.size {
+            match self.bulk.command(command(data.len())).await? {
+                Some(chunk) if !chunk.data.is_empty() => data.extend_from_slice(&chunk.data),
+                _ => {
+                    tracing::warn!("incomplete binary response, got {} of {} bytes",
+                        data.len(), first.size);
+                    return Ok(None);
+                }
+            }
+        }
+
+        Ok(Some(data))
+    }
 }
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use futures_channel::mpsc;
 use mpd_client::{
     client::ConnectionEvents,
@@ -6,55 +7,168 @@ use mpd_client::{
 
 pub use mpd_client::client::Subsystem;
 
-use crate::error::Error;
-use super::MpdCtrl;
+use crate::error::{AckCode, Error};
+use super::{
+    MpdCtrl,
+    MpdTarget,
+    mpd_target::Endpoint,
+    commands::{MusicDirectory, SwitchPartition},
+};
 
 #[derive(Debug, Clone)]
 pub enum MpdEvent {
     Connected(MpdCtrl),
     Change(Subsystem),
+    PartitionMissing(Error),
 }
 
 pub struct MpdEvents {
     client: Client,
     events: ConnectionEvents,
+    target: MpdTarget,
 }
 
 impl MpdEvents {
-    const TARGET: &str = "localhost:6600";
-    const BINARY_LIMIT: usize = 655360;
-
-    pub async fn open() -> Result<Self, Error> {
-        let stream = tokio::net::TcpStream::connect(Self::TARGET).await?;
-        let (client, events) = Client::connect(stream).await?;
+    pub async fn open(target: &MpdTarget) -> Result<Self, Error> {
+        tracing::info!("connecting to mpd at {}", target.endpoint);
+        let (client, events) = connect(target).await?;
 
-        Ok(MpdEvents { client, events })
+        Ok(MpdEvents {
+            client,
+            events,
+            target: target.clone(),
+        })
     }
 
-    pub async fn run(mut self, mut tx: mpsc::Sender<MpdEvent>) -> Result<(), Error> {
-        use iced::futures::SinkExt;
+    pub async fn run(
+        mut self,
+        binary_limit: usize,
+        mut tx: mpsc::Sender<MpdEvent>,
+    ) -> Result<(), Error> {
+        use std::pin::pin;
+        use iced::futures::{SinkExt, future::{self, Either}};
         use mpd_client::{
             commands,
             client::ConnectionEvent,
         };
 
+        let mut missing = None;
+        if let Some(partition) = &self.target.partition {
+            tracing::info!("switching to partition {partition}");
+            match self.client.command(SwitchPartition(partition)).await.map_err(Error::from) {
+                Ok(()) => (),
+                // deleted meanwhile, failing would only repeat on every reconnect
+                Err(error) if error.ack_code() == Some(AckCode::NoExist) => {
+                    tracing::warn!("partition {partition} is gone: {error}");
+                    missing = Some(error);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        // second connection for downloads, only its end is of interest as
+        // the first connection reports the same changes
+        let (bulk, mut bulk_events) = connect(&self.target).await?;
+
         // Set large binary limit for faster cover-art download
-        self.client.command(commands::SetBinaryLimit(Self::BINARY_LIMIT)).await?;
+        bulk.command(commands::SetBinaryLimit(binary_limit)).await?;
+
+        let music_dir = self.music_directory().await;
 
         // inform user, that we are connected and hand out a remote control
-        tx.send(MpdEvent::Connected(MpdCtrl::new(self.client.clone()))).await?;
+        let ctrl = MpdCtrl::new(self.client.clone(), bulk, music_dir);
+        tx.send(MpdEvent::Connected(ctrl)).await?;
+        if let Some(error) = missing {
+            tx.send(MpdEvent::PartitionMissing(error)).await?;
+        }
+
+        // listen for further events from mpd, losing either connection
+        // ends the session
+        loop {
+            let main = pin!(self.events.next());
+            let bulk = pin!(bulk_events.next());
 
-        // listen for further events from mpd
-        while let Some(ev) = self.events.next().await {
-            match ev {
-                ConnectionEvent::SubsystemChange(sub)
+            match future::select(main, bulk).await {
+                Either::Left((Some(ConnectionEvent::SubsystemChange(sub)), _))
                     => tx.send(MpdEvent::Change(sub)).await?,
 
-                ConnectionEvent::ConnectionClosed(error)
-                    => return Err(error.into()),
+                Either::Right((Some(ConnectionEvent::SubsystemChange(_)), _)) => (),
+
+                Either::Left((Some(ConnectionEvent::ConnectionClosed(error)), _)) => {
+                    return Err(error.into());
+                }
+
+                Either::Right((Some(ConnectionEvent::ConnectionClosed(error)), _)) => {
+                    tracing::warn!("lost the download connection: {error}");
+                    return Err(error.into());
+                }
+
+                Either::Left((None, _)) | Either::Right((None, _)) => return Err(Error::Disconnect),
             }
         }
+    }
+
+    async fn music_directory(&self) -> Option<PathBuf> {
+        if let Endpoint::Tcp(..) = self.target.endpoint {
+            return None;
+        }
 
-        Err(Error::Disconnect)
+        match self.client.command(MusicDirectory).await {
+            // the music directory may also be a URI of a remote storage
+            Ok(dir) => dir
+                .map(PathBuf::from)
+                .filter(|dir| dir.is_absolute()),
+
+            Err(error) => {
+                tracing::debug!("music directory not available: {error}");
+                None
+            }
+        }
     }
 }
+
+async fn connect(target: &MpdTarget) -> Result<(Client, ConnectionEvents), Error> {
+    use tokio::net::{TcpStream, UnixStream};
+
+    let password = target.password.as_deref();
+
+    let connection = match &target.endpoint {
+        Endpoint::Tcp(host, port) => {
+            let stream = TcpStream::connect((host.as_str(), *port)).await?;
+            Client::connect_with_password_opt(stream, password).await?
+        }
+
+        Endpoint::Unix(path) => {
+            let stream = UnixStream::connect(path).await?;
+            Client::connect_with_password_opt(stream, password).await?
+        }
+
+        Endpoint::Abstract(name) => {
+            let stream = connect_abstract(name)?;
+            Client::connect_with_password_opt(stream, password).await?
+        }
+    };
+
+    Ok(connection)
+}
+
+#[cfg(target_os = "linux")]
+fn connect_abstract(name: &str) -> Result<tokio::net::UnixStream, Error> {
+    use std::os::{
+        linux::net::SocketAddrExt,
+        unix::net::{SocketAddr, UnixStream},
+    };
+
+    // connecting a local socket does not block for long, so doing this
+    // synchronously is fine
+    let addr = SocketAddr::from_abstract_name(name)?;
+    let stream = UnixStream::connect_addr(&addr)?;
+    stream.set_nonblocking(true)?;
+
+    Ok(tokio::net::UnixStream::from_std(stream)?)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn connect_abstract(_name: &str) -> Result<tokio::net::UnixStream, Error> {
+    Err(std::io::Error::from(std::io::ErrorKind::Unsupported).into())
+}
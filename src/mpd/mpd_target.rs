@@ -0,0 +1,186 @@
+use std::{fmt, path::PathBuf};
+use crate::config::ConnectionConfig;
+
+// where to find the MPD server.
+//
+// The syntax follows the conventions of `mpc` and libmpdclient: a host
+// may be prefixed with `password@`, a host starting with `/` is the path
+// of a Unix domain socket and a host starting with `@` names a socket in
+// the abstract namespace (Linux only).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MpdTarget {
+    pub endpoint: Endpoint,
+    pub password: Option<String>,
+    pub partition: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    Tcp(String, u16),
+    Unix(PathBuf),
+    Abstract(String),
+}
+
+impl MpdTarget {
+    pub const DEFAULT_HOST: &str = "localhost";
+    pub const DEFAULT_PORT: u16 = 6600;
+
+    // determine the target from the given overrides, falling back to the
+    // configuration and finally to the defaults.
+    //
+    // The `MPD_HOST` and `MPD_PORT` environment variables rank between
+    // the overrides and the configuration if `env` is set. It should not
+    // be for a profile the user selected, which wins over the environment.
+    pub fn resolve(
+        host: Option<&str>,
+        port: Option<u16>,
+        config: &ConnectionConfig,
+        env: bool,
+    ) -> Result<Self, String> {
+        let var = |name| std::env::var(name)
+            .ok()
+            .filter(|_| env)
+            .filter(|value: &String| !value.is_empty());
+
+        let host = host
+            .map(str::to_owned)
+            .or_else(|| var("MPD_HOST"))
+            .or(config.host.clone())
+            .unwrap_or(Self::DEFAULT_HOST.to_owned());
+
+        let port = match port {
+            Some(port) => port,
+            None => match var("MPD_PORT") {
+                Some(port) => port
+                    .parse()
+                    .map_err(|_| format!("invalid MPD_PORT: {port}"))?,
+                None => config.port.unwrap_or(Self::DEFAULT_PORT),
+            },
+        };
+
+        Ok(Self::parse(&host, port).with_defaults(config))
+    }
+
+    pub fn from_config(config: &ConnectionConfig) -> Self {
+        let host = config.host.as_deref().unwrap_or(Self::DEFAULT_HOST);
+        let port = config.port.unwrap_or(Self::DEFAULT_PORT);
+
+        Self::parse(host, port).with_defaults(config)
+    }
+
+    fn with_defaults(mut self, config: &ConnectionConfig) -> Self {
+        if self.password.is_none() {
+            self.password = config.password.clone();
+        }
+        self.partition = config.partition.clone();
+        self
+    }
+
+    pub fn parse(host: &str, port: u16) -> Self {
+        // a leading '@' denotes an abstract socket, not a password
+        let (password, host) = match host.split_once('@') {
+            Some((password, host)) if !password.is_empty()
+                => (Some(password.to_owned()), host),
+
+            _ => (None, host),
+        };
+
+        let endpoint = if let Some(name) = host.strip_prefix('@') {
+            Endpoint::Abstract(name.to_owned())
+        } else if host.starts_with('/') {
+            Endpoint::Unix(PathBuf::from(host))
+        } else {
+            Endpoint::Tcp(host.to_owned(), port)
+        };
+
+        Self { endpoint, password, partition: None }
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Tcp(host, port) => write!(f, "{host}:{port}"),
+            Self::Unix(path) => write!(f, "{}", path.display()),
+            Self::Abstract(name) => write!(f, "@{name}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(host: &str) -> (Endpoint, Option<String>) {
+        let target = MpdTarget::parse(host, 6600);
+        (target.endpoint, target.password)
+    }
+
+    fn tcp(host: &str, port: u16) -> Endpoint {
+        Endpoint::Tcp(host.to_owned(), port)
+    }
+
+    #[test]
+    fn parse_tcp() {
+        assert_eq!(parse("localhost"), (tcp("localhost", 6600), None));
+        assert_eq!(parse("192.168.1.20"), (tcp("192.168.1.20", 6600), None));
+        assert_eq!(parse("::1"), (tcp("::1", 6600), None));
+        assert_eq!(MpdTarget::parse("musicbox", 6601).endpoint, tcp("musicbox", 6601));
+    }
+
+    #[test]
+    fn parse_unix() {
+        assert_eq!(parse("/run/mpd/socket"), (Endpoint::Unix("/run/mpd/socket".into()), None));
+    }
+
+    #[test]
+    fn parse_abstract() {
+        assert_eq!(parse("@mpd"), (Endpoint::Abstract("mpd".to_owned()), None));
+    }
+
+    #[test]
+    fn parse_password() {
+        let secret = Some(String::from("secret"));
+        assert_eq!(parse("secret@musicbox"), (tcp("musicbox", 6600), secret.clone()));
+        assert_eq!(
+            parse("secret@/run/mpd/socket"),
+            (Endpoint::Unix("/run/mpd/socket".into()), secret.clone()),
+        );
+        assert_eq!(parse("secret@@mpd"), (Endpoint::Abstract("mpd".to_owned()), secret.clone()));
+        // only the first '@' separates the password
+        assert_eq!(parse("secret@mpd@host"), (tcp("mpd@host", 6600), secret));
+    }
+
+    #[test]
+    fn parse_malformed() {
+        // nothing is rejected, but odd input ends up somewhere harmless
+        assert_eq!(parse(""), (tcp("", 6600), None));
+        assert_eq!(parse("@"), (Endpoint::Abstract(String::new()), None));
+        assert_eq!(parse("secret@"), (tcp("", 6600), Some(String::from("secret"))));
+        assert_eq!(parse("host:6601"), (tcp("host:6601", 6600), None));
+    }
+
+    #[test]
+    fn resolve_overrides() {
+        let config = ConnectionConfig {
+            host: Some(String::from("config@confighost")),
+            port: Some(6601),
+            password: Some(String::from("fallback")),
+            partition: Some(String::from("office")),
+        };
+
+        let resolved = MpdTarget::resolve(Some("musicbox"), Some(6602), &config, false).unwrap();
+        assert_eq!(resolved.endpoint, tcp("musicbox", 6602));
+        assert_eq!(resolved.password.as_deref(), Some("fallback"));
+        assert_eq!(resolved.partition.as_deref(), Some("office"));
+
+        // without `env` the configuration is used whatever MPD_HOST says
+        let resolved = MpdTarget::resolve(None, None, &config, false).unwrap();
+        assert_eq!(resolved.endpoint, tcp("confighost", 6601));
+        assert_eq!(resolved.password.as_deref(), Some("config"));
+
+        let resolved = MpdTarget::resolve(None, None, &ConnectionConfig::default(), false).unwrap();
+        assert_eq!(resolved.endpoint, tcp(MpdTarget::DEFAULT_HOST, MpdTarget::DEFAULT_PORT));
+        assert_eq!(resolved.password, None);
+    }
+}
@@ -0,0 +1,35 @@
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Default)]
+pub struct Permissions {
+    allowed: HashSet<String>,
+    denied: HashSet<String>,
+}
+
+impl Permissions {
+    pub fn new(allowed: Vec<String>, denied: Vec<String>) -> Self {
+        Self {
+            allowed: allowed.into_iter().collect(),
+            denied: denied.into_iter().collect(),
+        }
+    }
+
+    // permissions used before the server told us: assume everything is
+    // allowed, MPD will reject what is not.
+    pub fn unknown() -> Self {
+        Self::default()
+    }
+
+    pub fn allows(&self, command: &str) -> bool {
+        if self.allowed.is_empty() {
+            !self.denied.contains(command)
+        } else {
+            self.allowed.contains(command)
+        }
+    }
+
+    pub fn is_restricted(&self) -> bool {
+        const CONTROL: [&str; 3] = ["play", "pause", "setvol"];
+        CONTROL.iter().any(|cmd| self.denied.contains(*cmd))
+    }
+}